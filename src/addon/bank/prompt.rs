@@ -1,50 +1,65 @@
 use crate::utils::logger::{LogLevel, Logger};
 use crate::{
     addon::bank::scaffold::scaffold_bank,
-    utils::{kebab_case::to_kebab_case, spinner::with_spinner},
+    addon::template::{self, TemplateKind},
+    utils::{kebab_case::to_kebab_case, prompt, spinner::with_spinner},
 };
+use std::path::PathBuf;
+
+/// Values given on the command line for `bank create`; missing ones are prompted for.
+#[derive(Debug, Clone, Default)]
+pub struct BankCreateOptions {
+    pub name: Option<String>,
+    pub publisher: Option<String>,
+    pub description: Option<String>,
+    pub template: Option<String>,
+    /// Skip the confirmation prompt
+    pub yes: bool,
+}
 
 /// Prompts the user for bank details and creates a new bank.
 ///
 /// ### Parameters
 /// - `cwd`: The current directory
+/// - `options`: Values given as flags; only the missing ones are prompted for
 ///
-pub async fn prompt_bank_addon(cwd: &str) -> Result<(), String> {
+pub async fn prompt_bank_addon(cwd: &str, options: BankCreateOptions) -> Result<(), String> {
+    // Fail before prompting when the requested template does not exist
+    let template_dir = match options.template {
+        Some(spec) => Some(template::resolve_template(cwd, TemplateKind::Bank, &spec)?),
+        None => None,
+    };
+
     println!();
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!("Devalang Bank Packager");
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!();
 
-    let final_name = match inquire::Text::new("Enter the bank name:")
-        .with_default("mybank")
-        .prompt()
-    {
-        Ok(name) => to_kebab_case(&name).replace("-", ""),
-        Err(e) => {
-            return Err(format!("Failed to prompt for bank name: {}", e));
-        }
+    let template_dir = match template_dir {
+        Some(dir) => Some(dir),
+        None => prompt_bank_template(cwd)?,
     };
 
-    let final_publisher = match inquire::Text::new("Enter the bank publisher:")
-        .with_default("johndoe")
-        .prompt()
-    {
-        Ok(publisher) => to_kebab_case(&publisher),
-        Err(e) => {
-            return Err(format!("Failed to prompt for bank publisher: {}", e));
-        }
-    };
+    let final_name = prompt::text(options.name, "name", "Enter the bank name:", "mybank", true)
+        .map(|name| to_kebab_case(&name).replace("-", ""))?;
 
-    let final_description = match inquire::Text::new("Enter the bank description:")
-        .with_default("A description of my bank")
-        .prompt()
-    {
-        Ok(description) => description.to_string(),
-        Err(e) => {
-            return Err(format!("Failed to prompt for bank description: {}", e));
-        }
-    };
+    let final_publisher = prompt::text(
+        options.publisher,
+        "publisher",
+        "Enter the bank publisher:",
+        "johndoe",
+        true,
+    )
+    .map(|publisher| to_kebab_case(&publisher))?;
+
+    let final_description = prompt::text(
+        options.description,
+        "description",
+        "Enter the bank description:",
+        "A description of my bank",
+        false,
+    )?;
 
     // TODO Enable this when we support private/protected banks
     // let options = vec!["public", "private", "protected"];
@@ -77,15 +92,14 @@ pub async fn prompt_bank_addon(cwd: &str) -> Result<(), String> {
         &format!("Description: {}", final_description),
     );
     Logger::new().log_message(LogLevel::Info, &format!("Access Level: {}", final_access));
+    if let Some(dir) = &template_dir {
+        Logger::new().log_message(LogLevel::Info, &format!("Template: {}", dir.display()));
+    }
 
     println!();
 
-    let confirm_prompt = inquire::Confirm::new("Are these details correct ?")
-        .with_default(true)
-        .prompt();
-
-    match confirm_prompt {
-        Ok(true) => {
+    match prompt::confirm(options.yes, "Are these details correct ?")? {
+        true => {
             let spinner = with_spinner("Generating bank...");
 
             let res = scaffold_bank(
@@ -94,14 +108,37 @@ pub async fn prompt_bank_addon(cwd: &str) -> Result<(), String> {
                 final_publisher,
                 final_description,
                 final_access,
+                template_dir.as_deref(),
             )
             .await;
             spinner.finish_and_clear();
             res
         }
-        _ => {
+        false => {
             Logger::new().log_message(LogLevel::Warning, "Aborting bank scaffolding.");
             Err("aborted by user".into())
         }
     }
 }
+
+/// Offers the discovered bank templates; returns `None` for the default layout, when
+/// there are no templates or when stdin is not a terminal.
+///
+/// ### Parameters
+/// - `cwd`: The current directory
+///
+fn prompt_bank_template(cwd: &str) -> Result<Option<PathBuf>, String> {
+    let templates = template::discover_templates(cwd, TemplateKind::Bank);
+    if templates.is_empty() || !prompt::is_interactive() {
+        return Ok(None);
+    }
+
+    let mut options = vec!["default".to_string()];
+    options.extend(templates.iter().map(|t| format!("{} (template)", t.name)));
+
+    match inquire::Select::new("Select the bank template:", options).raw_prompt() {
+        Ok(choice) if choice.index == 0 => Ok(None),
+        Ok(choice) => Ok(Some(templates[choice.index - 1].path.clone())),
+        Err(e) => Err(format!("Failed to prompt for bank template: {}", e)),
+    }
+}
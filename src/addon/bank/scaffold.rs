@@ -1,3 +1,5 @@
+use crate::addon::template;
+use crate::builder::readme;
 use std::path::Path;
 
 /// Scaffold a new bank with the given parameters.
@@ -8,6 +10,7 @@ use std::path::Path;
 /// - `publisher`: The publisher of the bank.
 /// - `description`: A brief description of the bank.
 /// - `access`: The access level of the bank.
+/// - `template`: A template directory to copy first; files it lacks are generated.
 ///
 pub async fn scaffold_bank(
     cwd: &str,
@@ -15,6 +18,7 @@ pub async fn scaffold_bank(
     publisher: String,
     description: String,
     access: String,
+    template: Option<&Path>,
 ) -> Result<(), String> {
     let banks_root = Path::new(cwd).join("generated").join("banks");
 
@@ -35,17 +39,33 @@ pub async fn scaffold_bank(
         return Err(format!("Failed to create bank directory: {}", e));
     }
 
+    if let Some(dir) = template {
+        let vars = [
+            ("name", name.as_str()),
+            ("publisher", publisher.as_str()),
+            ("description", description.as_str()),
+        ];
+        if let Err(e) = template::copy_template(dir, &bank_path, &vars) {
+            crate::utils::logger::Logger::new().log_message(
+                crate::utils::logger::LogLevel::Error,
+                &format!("Error copying template {}: {}", dir.display(), e),
+            );
+            return Err(format!("Failed to copy template: {}", e));
+        }
+    }
+
     let audio_path = "audio/";
 
-    if let Err(e) = create_bank_toml(
-        &bank_path,
-        name.as_str(),
-        publisher.as_str(),
-        description.as_str(),
-        audio_path,
-        access.as_str(),
-    )
-    .await
+    if !bank_path.join("bank.toml").exists()
+        && let Err(e) = create_bank_toml(
+            &bank_path,
+            name.as_str(),
+            publisher.as_str(),
+            description.as_str(),
+            audio_path,
+            access.as_str(),
+        )
+        .await
     {
         crate::utils::logger::Logger::new().log_message(
             crate::utils::logger::LogLevel::Error,
@@ -149,9 +169,13 @@ async fn write_default_docs(
     // README.md
     let readme_path = bank_path.join("README.md");
     if !readme_path.exists() {
+        // The marked block is filled with the trigger table on every build
         let readme = format!(
-            "# {}.{} Bank\n\n{}\n\nContents:\n- bank.toml\n- audio/ (assets)\n- LICENSE\n\nBuilt with devapack.\n",
-            publisher, name, description
+            "# {}.{} Bank\n\n{}\n\nContents:\n- bank.toml\n- audio/ (assets)\n- LICENSE\n\nBuilt with devapack.\n\n{}",
+            publisher,
+            name,
+            description,
+            readme::generated_block("_Run `devapack bank build` to list the triggers here._")
         );
         std::fs::write(&readme_path, readme)
             .map_err(|e| format!("Failed to write README.md: {}", e))?;
@@ -1,8 +1,7 @@
 pub mod bank;
 pub mod plugin;
-pub mod publish;
-pub mod remote_sign;
 pub mod self_sign;
 pub mod submit;
 pub mod summary;
+pub mod template;
 pub mod update;
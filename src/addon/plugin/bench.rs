@@ -0,0 +1,574 @@
+use crate::builder::plugin::resolve_plugin_dir;
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    spinner,
+    version::get_version,
+};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use wasmtime::{Engine, Linker, Memory, Module, ResourceLimiter, Store, TypedFunc};
+
+/// Signature shared by every audio-producing export generated by `export_plugin!`:
+/// `(out_ptr, out_len, freq, amp, duration_ms, sample_rate, channels)`.
+type RenderParams = (i32, i32, f32, f32, i32, i32, i32);
+
+const WASM_PAGE_SIZE: usize = 65536;
+const BENCH_FREQ: f32 = 440.0;
+const BENCH_AMP: f32 = 0.8;
+
+#[derive(Debug, Deserialize, Default)]
+struct PluginSection {
+    name: Option<String>,
+    publisher: Option<String>,
+    version: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ExportEntry {
+    name: String,
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PluginTomlDoc {
+    plugin: Option<PluginSection>,
+    #[serde(default)]
+    exports: Vec<ExportEntry>,
+}
+
+/// Options controlling the benchmark matrix.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub iterations: usize,
+    pub blocks: usize,
+    pub sample_rates: Vec<u32>,
+    pub buffer_sizes: Vec<u32>,
+    pub channels: u32,
+}
+
+/// Results of one export at one sample rate and buffer size.
+///
+/// The `*_block_us` statistics are over every measured block (`iterations` × `blocks`
+/// samples), so they show the jitter of single blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchCase {
+    pub export: String,
+    pub sample_rate: u32,
+    pub buffer_size: u32,
+    pub channels: u32,
+    pub iterations: usize,
+    pub blocks: usize,
+    pub min_block_us: f64,
+    pub mean_block_us: f64,
+    pub median_block_us: f64,
+    pub p95_block_us: f64,
+    pub stddev_block_us: f64,
+    pub worst_block_us: f64,
+    pub realtime_factor: f64,
+    pub worst_realtime_factor: f64,
+    pub overruns: usize,
+    /// Times the linear memory grew after warm-up. This is not an allocation count: the
+    /// plugin's allocator serves most allocations from memory it already has, unseen
+    #[serde(alias = "memory_grow_events")]
+    pub linear_memory_grows: u64,
+    /// Bytes the linear memory grew by after warm-up
+    #[serde(alias = "memory_grown_bytes")]
+    pub linear_memory_grown_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub plugin: String,
+    pub version: Option<String>,
+    pub devapack_version: String,
+    pub created_at: String,
+    pub wasm_sha256: String,
+    pub cases: Vec<BenchCase>,
+}
+
+/// Store data used to count linear-memory growth, the only sign of the plugin's
+/// allocations visible from outside the module.
+#[derive(Default)]
+struct BenchState {
+    counting: bool,
+    grow_events: u64,
+    grown_bytes: u64,
+}
+
+impl ResourceLimiter for BenchState {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        if self.counting {
+            self.grow_events += 1;
+            self.grown_bytes += desired.saturating_sub(current) as u64;
+        }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Benchmarks every audio-producing export of a built plugin.
+///
+/// ### Parameters
+/// - `path`: Relative path or alias `plugin.<publisher>.<name>`
+/// - `cwd`: The current working directory
+/// - `options`: The benchmark matrix (sample rates, buffer sizes, iterations)
+///
+pub fn bench_plugin(path: &str, cwd: &str, options: &BenchOptions) -> Result<(), String> {
+    if options.iterations == 0 || options.blocks == 0 {
+        return Err("--iterations and --blocks must be greater than zero".into());
+    }
+    if options.sample_rates.is_empty() || options.buffer_sizes.is_empty() {
+        return Err("At least one sample rate and one buffer size are required".into());
+    }
+
+    let plugin_dir = spinner::run_step(
+        &format!("Resolving plugin directory for '{}'", path),
+        |dir: &PathBuf| format!("Using {}", dir.to_string_lossy()),
+        || resolve_plugin_dir(cwd, path),
+    )?;
+
+    let doc: PluginTomlDoc = {
+        let txt = fs::read_to_string(plugin_dir.join("plugin.toml"))
+            .map_err(|e| format!("Failed to read plugin.toml: {}", e))?;
+        toml::from_str(&txt).map_err(|e| format!("Invalid TOML: {}", e))?
+    };
+    let section = doc.plugin.unwrap_or_default();
+    let publisher = section.publisher.unwrap_or_default();
+    let name = section.name.unwrap_or_default();
+    if publisher.trim().is_empty() || name.trim().is_empty() {
+        return Err(
+            "Fields [plugin].publisher and [plugin].name are required in plugin.toml".into(),
+        );
+    }
+    let plugin_id = format!("{}.{}", publisher, name);
+
+    let archive = Path::new(cwd)
+        .join("output")
+        .join("plugin")
+        .join(format!("{}.tar.gz", plugin_id));
+    let wasm_bytes = spinner::run_step(
+        &format!("Reading wasm from {}", archive.display()),
+        |bytes: &Vec<u8>| format!("Loaded wasm ({} bytes)", bytes.len()),
+        || read_wasm_from_archive(&archive),
+    )?;
+
+    let engine = Engine::default();
+    let module = spinner::run_step(
+        "Compiling wasm module",
+        |_| "Module compiled".to_string(),
+        || Module::new(&engine, &wasm_bytes).map_err(|e| format!("Failed to compile wasm: {}", e)),
+    )?;
+
+    let exports = discover_render_exports(&engine, &module, &doc.exports)?;
+    if exports.is_empty() {
+        return Err(format!(
+            "No audio-producing exports found in {} (expected export_plugin! style functions)",
+            plugin_id
+        ));
+    }
+
+    let mut cases: Vec<BenchCase> = Vec::new();
+    for export in &exports {
+        for sample_rate in &options.sample_rates {
+            for buffer_size in &options.buffer_sizes {
+                let case = spinner::run_step(
+                    &format!(
+                        "Benchmarking {} @ {} Hz / {} frames",
+                        export, sample_rate, buffer_size
+                    ),
+                    |c: &BenchCase| {
+                        format!(
+                            "{} @ {} Hz / {} frames: RTF {:.4}",
+                            c.export, c.sample_rate, c.buffer_size, c.realtime_factor
+                        )
+                    },
+                    || {
+                        run_case(
+                            &engine,
+                            &module,
+                            export,
+                            *sample_rate,
+                            *buffer_size,
+                            options,
+                        )
+                    },
+                )?;
+                cases.push(case);
+            }
+        }
+    }
+
+    let bench_dir = Path::new(cwd).join("output").join("bench").join(&plugin_id);
+    let previous = load_previous_report(&bench_dir);
+
+    let report = BenchReport {
+        plugin: plugin_id.clone(),
+        version: section.version,
+        devapack_version: get_version(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        wasm_sha256: hex::encode(Sha256::digest(&wasm_bytes)),
+        cases,
+    };
+
+    print_report(&report, previous.as_ref());
+
+    fs::create_dir_all(&bench_dir)
+        .map_err(|e| format!("Failed to create {}: {}", bench_dir.display(), e))?;
+    let out_file = bench_dir.join(format!(
+        "{}.json",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize benchmark report: {}", e))?;
+    fs::write(&out_file, json)
+        .map_err(|e| format!("Failed to write {}: {}", out_file.display(), e))?;
+
+    Logger::new().log_message(
+        LogLevel::Success,
+        &format!("Benchmark saved to {}", out_file.to_string_lossy()),
+    );
+
+    Ok(())
+}
+
+/// Extracts the first `.wasm` entry from a plugin archive.
+///
+/// ### Parameters
+/// - `archive`: The path to `output/plugin/<publisher>.<name>.tar.gz`
+///
+fn read_wasm_from_archive(archive: &Path) -> Result<Vec<u8>, String> {
+    if !archive.exists() {
+        return Err(format!(
+            "Plugin archive not found at {}; run 'devapack plugin build' first",
+            archive.display()
+        ));
+    }
+    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let entries = tar
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let is_wasm = entry
+            .path()
+            .ok()
+            .and_then(|p| p.extension().map(|e| e == "wasm"))
+            .unwrap_or(false);
+        if is_wasm {
+            let mut bytes = Vec::new();
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read wasm from archive: {}", e))?;
+            return Ok(bytes);
+        }
+    }
+    Err(format!("No .wasm artifact found in {}", archive.display()))
+}
+
+/// Lists the exports that match the render signature, preferring the manifest's `[[exports]]`.
+///
+/// ### Parameters
+/// - `engine`: The wasmtime engine
+/// - `module`: The compiled plugin module
+/// - `declared`: Exports declared in plugin.toml
+///
+fn discover_render_exports(
+    engine: &Engine,
+    module: &Module,
+    declared: &[ExportEntry],
+) -> Result<Vec<String>, String> {
+    let mut candidates: Vec<String> = declared
+        .iter()
+        .filter(|e| e.kind.as_deref().unwrap_or("func") == "func")
+        .map(|e| e.name.clone())
+        .collect();
+    if candidates.is_empty() {
+        candidates = module
+            .exports()
+            .filter(|e| e.ty().func().is_some())
+            .map(|e| e.name().to_string())
+            .collect();
+    }
+
+    candidates.sort();
+    candidates.dedup();
+
+    let mut exports = Vec::new();
+    for name in candidates {
+        if writes_audio(engine, module, &name)? {
+            exports.push(name);
+        }
+    }
+    Ok(exports)
+}
+
+/// Probes an export once to check it matches the render signature and writes to the buffer.
+///
+/// Setters such as `gain` or `reset` share the signature but leave the buffer untouched,
+/// so they are skipped. Effects are detected by feeding a test tone and checking it changed.
+fn writes_audio(engine: &Engine, module: &Module, export: &str) -> Result<bool, String> {
+    let (mut store, instance) = instantiate(engine, module)?;
+    let Ok(func) = instance.get_typed_func::<RenderParams, ()>(&mut store, export) else {
+        return Ok(false);
+    };
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| "WASM memory export not found".to_string())?;
+
+    let (sample_rate, frames, channels) = (48000, 256usize, 2usize);
+    let samples = frames * channels;
+    let byte_len = samples * std::mem::size_of::<f32>();
+    let ptr = alloc_buffer(&mut store, &memory, byte_len)?;
+    let args: RenderParams = (
+        ptr as i32,
+        samples as i32,
+        BENCH_FREQ,
+        BENCH_AMP,
+        10,
+        sample_rate,
+        channels as i32,
+    );
+
+    let tone: Vec<u8> = (0..samples)
+        .flat_map(|i| {
+            let t = (i / channels) as f32 / sample_rate as f32;
+            (0.5 * (std::f32::consts::TAU * BENCH_FREQ * t).sin()).to_le_bytes()
+        })
+        .collect();
+
+    for input in [vec![0u8; byte_len], tone] {
+        memory.data_mut(&mut store)[ptr..ptr + byte_len].copy_from_slice(&input);
+        // A trapping export is not a usable render function; skip it rather than abort.
+        if func.call(&mut store, args).is_err() {
+            return Ok(false);
+        }
+        if memory.data(&store)[ptr..ptr + byte_len] != input[..] {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn instantiate(
+    engine: &Engine,
+    module: &Module,
+) -> Result<(Store<BenchState>, wasmtime::Instance), String> {
+    let mut store = Store::new(engine, BenchState::default());
+    store.limiter(|state| state);
+    let mut linker: Linker<BenchState> = Linker::new(engine);
+    linker
+        .define_unknown_imports_as_traps(module)
+        .map_err(|e| format!("Failed to define plugin imports: {}", e))?;
+    let instance = linker
+        .instantiate(&mut store, module)
+        .map_err(|e| format!("Failed to instantiate wasm: {}", e))?;
+    Ok((store, instance))
+}
+
+/// Runs one export at one sample rate / buffer size and collects timing statistics.
+fn run_case(
+    engine: &Engine,
+    module: &Module,
+    export: &str,
+    sample_rate: u32,
+    buffer_size: u32,
+    options: &BenchOptions,
+) -> Result<BenchCase, String> {
+    let (mut store, instance) = instantiate(engine, module)?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| "WASM memory export not found".to_string())?;
+    let func: TypedFunc<RenderParams, ()> = instance
+        .get_typed_func(&mut store, export)
+        .map_err(|e| format!("Export '{}' has an unexpected signature: {}", export, e))?;
+
+    // The render call takes the sample count as an i32
+    let samples = buffer_size
+        .checked_mul(options.channels)
+        .and_then(|n| i32::try_from(n).ok())
+        .ok_or_else(|| {
+            format!(
+                "A buffer of {} frames x {} channels is too large to render",
+                buffer_size, options.channels
+            )
+        })? as usize;
+    let byte_len = samples * std::mem::size_of::<f32>();
+    let ptr = alloc_buffer(&mut store, &memory, byte_len)?;
+
+    let block_secs = buffer_size as f64 / sample_rate as f64;
+    let duration_ms = (block_secs * 1000.0).ceil() as i32;
+    let args: RenderParams = (
+        ptr as i32,
+        samples as i32,
+        BENCH_FREQ,
+        BENCH_AMP,
+        duration_ms,
+        sample_rate as i32,
+        options.channels as i32,
+    );
+
+    let render_block = |store: &mut Store<BenchState>| -> Result<f64, String> {
+        memory.data_mut(&mut *store)[ptr..ptr + byte_len].fill(0);
+        let start = Instant::now();
+        func.call(&mut *store, args)
+            .map_err(|e| format!("Error calling '{}': {}", export, e))?;
+        Ok(start.elapsed().as_secs_f64() * 1e6)
+    };
+
+    // Warm up caches and lazily initialised plugin state before measuring.
+    for _ in 0..(options.blocks / 8).max(1) {
+        render_block(&mut store)?;
+    }
+
+    store.data_mut().counting = true;
+    let budget_us = block_secs * 1e6;
+    let mut block_times: Vec<f64> = Vec::with_capacity(options.iterations * options.blocks);
+    for _ in 0..options.iterations * options.blocks {
+        block_times.push(render_block(&mut store)?);
+    }
+    store.data_mut().counting = false;
+
+    let overruns = block_times.iter().filter(|t| **t > budget_us).count();
+    let mut sorted = block_times;
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let worst_block_us = sorted[sorted.len() - 1];
+    let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+    let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / sorted.len() as f64;
+
+    Ok(BenchCase {
+        export: export.to_string(),
+        sample_rate,
+        buffer_size,
+        channels: options.channels,
+        iterations: options.iterations,
+        blocks: options.blocks,
+        min_block_us: sorted[0],
+        mean_block_us: mean,
+        median_block_us: percentile(&sorted, 0.5),
+        p95_block_us: percentile(&sorted, 0.95),
+        stddev_block_us: variance.sqrt(),
+        worst_block_us,
+        realtime_factor: mean / budget_us,
+        worst_realtime_factor: worst_block_us / budget_us,
+        overruns,
+        linear_memory_grows: store.data().grow_events,
+        linear_memory_grown_bytes: store.data().grown_bytes,
+    })
+}
+
+/// Reserves host-owned scratch space at the end of linear memory, mirroring Devalang's runner.
+fn alloc_buffer(
+    store: &mut Store<BenchState>,
+    memory: &Memory,
+    byte_len: usize,
+) -> Result<usize, String> {
+    let ptr = memory.data_size(&*store);
+    let pages = byte_len.div_ceil(WASM_PAGE_SIZE) as u64;
+    memory
+        .grow(&mut *store, pages)
+        .map_err(|e| format!("Failed to grow memory: {}", e))?;
+    Ok(ptr)
+}
+
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+/// Loads the most recent stored report for comparison.
+///
+/// ### Parameters
+/// - `bench_dir`: The directory `output/bench/<publisher>.<name>`
+///
+fn load_previous_report(bench_dir: &Path) -> Option<BenchReport> {
+    let mut files: Vec<PathBuf> = fs::read_dir(bench_dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    files.sort();
+    let latest = files.pop()?;
+    let txt = fs::read_to_string(latest).ok()?;
+    serde_json::from_str(&txt).ok()
+}
+
+fn print_report(report: &BenchReport, previous: Option<&BenchReport>) {
+    let logger = Logger::new();
+    let mut lines: Vec<String> = Vec::new();
+    for c in &report.cases {
+        let mut line = format!(
+            "{} @ {} Hz / {} frames: {:.2} µs/block (p95 {:.2}, ±{:.2}) • RTF {:.4} (worst {:.4}) • overruns {} • linear memory grows {}",
+            c.export,
+            c.sample_rate,
+            c.buffer_size,
+            c.mean_block_us,
+            c.p95_block_us,
+            c.stddev_block_us,
+            c.realtime_factor,
+            c.worst_realtime_factor,
+            c.overruns,
+            c.linear_memory_grows
+        );
+        let prev_case = previous.and_then(|p| {
+            p.cases.iter().find(|pc| {
+                pc.export == c.export
+                    && pc.sample_rate == c.sample_rate
+                    && pc.buffer_size == c.buffer_size
+                    && pc.channels == c.channels
+            })
+        });
+        if let Some(pc) = prev_case.filter(|pc| pc.mean_block_us > 0.0) {
+            let delta = (c.mean_block_us - pc.mean_block_us) / pc.mean_block_us * 100.0;
+            line.push_str(&format!(" • {:+.1}% vs previous", delta));
+        }
+        lines.push(line);
+    }
+    let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+    logger.log_message_with_trace(
+        LogLevel::Info,
+        &format!("Benchmark results for {}", report.plugin),
+        refs,
+    );
+
+    let worst = report
+        .cases
+        .iter()
+        .map(|c| c.realtime_factor)
+        .fold(0.0f64, f64::max);
+    if worst >= 1.0 {
+        logger.log_message(
+            LogLevel::Warning,
+            &format!(
+                "Plugin is slower than real time (RTF {:.3}); it will drop out in playback",
+                worst
+            ),
+        );
+    }
+    if report.cases.iter().any(|c| c.linear_memory_grows > 0) {
+        logger.log_message(
+            LogLevel::Warning,
+            "Plugin linear memory kept growing after warm-up, so it allocates in the render path",
+        );
+    }
+}
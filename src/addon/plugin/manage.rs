@@ -1,4 +1,8 @@
-use crate::utils::semver;
+use crate::addon::plugin::workspace::unregister_plugin;
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    semver,
+};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -21,8 +25,8 @@ struct PluginTomlDoc {
 pub fn list_plugins(cwd: &str) -> Result<(), String> {
     let root = Path::new(cwd).join("generated").join("plugins");
     if !root.exists() {
-        crate::utils::logger::Logger::new().log_message(
-            crate::utils::logger::LogLevel::Info,
+        Logger::new().log_message(
+            LogLevel::Info,
             &format!("No plugins directory at {}", root.to_string_lossy()),
         );
         return Ok(());
@@ -45,8 +49,8 @@ pub fn list_plugins(cwd: &str) -> Result<(), String> {
         }
     }
     if entries.is_empty() {
-        crate::utils::logger::Logger::new().log_message(
-            crate::utils::logger::LogLevel::Info,
+        Logger::new().log_message(
+            LogLevel::Info,
             &format!("No plugins found in {}", root.to_string_lossy()),
         );
         return Ok(());
@@ -65,8 +69,8 @@ pub fn list_plugins(cwd: &str) -> Result<(), String> {
         let version = pl.version.unwrap_or_else(|| "?".into());
         let access = pl.access.unwrap_or_else(|| "?".into());
         let description = pl.description.unwrap_or_default();
-        crate::utils::logger::Logger::new().log_message(
-            crate::utils::logger::LogLevel::Info,
+        Logger::new().log_message(
+            LogLevel::Info,
             &format!(
                 "- {}.{}  v{}  [{}]  {}",
                 publisher, name, version, access, description
@@ -76,6 +80,117 @@ pub fn list_plugins(cwd: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Deletes a generated plugin, its build artifacts and its workspace exclude entry.
+///
+/// ### Parameters
+/// - `cwd`: current working directory.
+/// - `id`: plugin identifier `<publisher>.<name>` (or `plugin.<publisher>.<name>`).
+/// - `yes`: skip the confirmation prompt.
+///
+pub fn delete_plugin(cwd: &str, id: &str, yes: bool) -> Result<(), String> {
+    let id = id.strip_prefix("plugin.").unwrap_or(id);
+    // Both parts become path components, so nothing may climb out of generated/plugins
+    let is_component = |s: &str| !s.is_empty() && s != "." && s != ".." && !s.contains(['/', '\\']);
+    let (publisher, name) = id
+        .split_once('.')
+        .filter(|(p, n)| is_component(p) && is_component(n))
+        .ok_or_else(|| format!("Invalid plugin id '{}'; expected <publisher>.<name>", id))?;
+
+    let plugins_root = Path::new(cwd).join("generated").join("plugins");
+    let plugin_dir = plugins_root.join(publisher).join(name);
+    if !plugin_dir.join("plugin.toml").is_file() {
+        return Err(format!(
+            "Plugin '{}' not found under {} (no plugin.toml)",
+            id,
+            plugins_root.to_string_lossy()
+        ));
+    }
+    let canonical = |p: &Path| {
+        p.canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", p.to_string_lossy(), e))
+    };
+    if !canonical(&plugin_dir)?.starts_with(canonical(&plugins_root)?.join(publisher)) {
+        return Err(format!(
+            "Refusing to delete {}: it resolves outside {}",
+            plugin_dir.to_string_lossy(),
+            plugins_root.to_string_lossy()
+        ));
+    }
+
+    // The archive, its signatures, the source archive and the build cache entry; matching
+    // the exact names leaves alone plugins whose name starts with this one's (`<name>.<x>`)
+    let out_dir = Path::new(cwd).join("output").join("plugin");
+    let mut artifacts: Vec<PathBuf> = ["tar.gz", "tar.gz.sig", "tar.gz.minisig", "src.tar.gz"]
+        .iter()
+        .map(|ext| out_dir.join(format!("{}.{}.{}", publisher, name, ext)))
+        .collect();
+    artifacts.push(crate::builder::cache::entry_path(
+        cwd,
+        "plugin",
+        &plugin_dir,
+    ));
+    artifacts.retain(|p| p.is_file());
+    let bench_dir = Path::new(cwd)
+        .join("output")
+        .join("bench")
+        .join(format!("{}.{}", publisher, name));
+
+    let mut targets: Vec<String> = vec![plugin_dir.to_string_lossy().to_string()];
+    targets.extend(artifacts.iter().map(|p| p.to_string_lossy().to_string()));
+    if bench_dir.is_dir() {
+        targets.push(bench_dir.to_string_lossy().to_string());
+    }
+    let trace: Vec<&str> = targets.iter().map(|s| s.as_str()).collect();
+    Logger::new().log_message_with_trace(
+        LogLevel::Warning,
+        &format!("The following will be deleted for {}:", id),
+        trace,
+    );
+
+    if !yes {
+        let confirmed = inquire::Confirm::new(&format!("Delete plugin {} ?", id))
+            .with_default(false)
+            .prompt()
+            .map_err(|e| {
+                format!(
+                    "Confirmation required ({}); pass --yes to delete without prompting",
+                    e
+                )
+            })?;
+        if !confirmed {
+            Logger::new().log_message(LogLevel::Warning, "Aborting plugin deletion.");
+            return Ok(());
+        }
+    }
+
+    // Unregister first, so a failure below does not leave a member pointing at nothing
+    if unregister_plugin(cwd, publisher, name)? {
+        Logger::new().log_message(
+            LogLevel::Info,
+            &format!("Removed {} from the Cargo workspace manifests", id),
+        );
+    }
+
+    fs::remove_dir_all(&plugin_dir)
+        .map_err(|e| format!("Failed to remove {}: {}", plugin_dir.to_string_lossy(), e))?;
+    for a in &artifacts {
+        fs::remove_file(a)
+            .map_err(|e| format!("Failed to remove {}: {}", a.to_string_lossy(), e))?;
+    }
+    if bench_dir.is_dir() {
+        fs::remove_dir_all(&bench_dir)
+            .map_err(|e| format!("Failed to remove {}: {}", bench_dir.to_string_lossy(), e))?;
+    }
+
+    // Remove now-empty publisher directory so `plugin list` stays tidy
+    if let Some(pub_dir) = plugin_dir.parent() {
+        let _ = fs::remove_dir(pub_dir);
+    }
+
+    Logger::new().log_message(LogLevel::Success, &format!("✅ Deleted plugin: {}", id));
+    Ok(())
+}
+
 /// Bumps the version of a plugin.
 pub fn bump_version(cwd: &str, id: &str, bump: &str) -> Result<(), String> {
     // accept id in form <publisher>.<name>
@@ -117,8 +232,8 @@ pub fn bump_version(cwd: &str, id: &str, bump: &str) -> Result<(), String> {
     let updated = write_version_in_plugin_toml(&content, &new_version)?;
     fs::write(&path, updated)
         .map_err(|e| format!("Failed to write {}: {}", path.to_string_lossy(), e))?;
-    crate::utils::logger::Logger::new().log_message(
-        crate::utils::logger::LogLevel::Success,
+    Logger::new().log_message(
+        LogLevel::Success,
         &format!("✅ {} -> {}", current, new_version),
     );
     Ok(())
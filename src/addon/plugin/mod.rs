@@ -1,4 +1,6 @@
+pub mod bench;
 pub mod manage;
 pub mod preset;
 pub mod prompt;
 pub mod scaffold;
+pub mod workspace;
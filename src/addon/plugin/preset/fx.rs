@@ -0,0 +1,221 @@
+use std::path::Path;
+
+/// Exports declared in the generated plugin.toml.
+pub const EXPORTS: &[&str] = &[
+    "process",
+    "reset",
+    "set_cutoff",
+    "set_delay_ms",
+    "set_feedback",
+    "set_mix",
+];
+
+pub async fn create_plugin_src_fx(src_path: &Path) -> Result<(), String> {
+    if let Err(e) = std::fs::create_dir_all(src_path) {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating plugin src directory: {}", e),
+        );
+        return Err(format!("Failed to create plugin src directory: {}", e));
+    }
+
+    if let Err(e) = create_plugin_fx_src_lib(src_path).await {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating plugin src/lib.rs: {}", e),
+        );
+        return Err(format!("Failed to create plugin src/lib.rs: {}", e));
+    }
+
+    Ok(())
+}
+
+/// README section explaining how to chain the fx plugin in Devalang.
+///
+/// ### Parameters
+/// - `publisher`: The plugin publisher
+/// - `name`: The plugin name
+///
+pub fn readme_section(publisher: &str, name: &str) -> String {
+    format!(
+        r#"## Usage in Devalang
+
+The `process` export transforms the buffer it receives in place: a feedback delay whose repeats
+go through a one-pole low-pass filter, mixed back with the dry signal.
+
+```deva
+@use {publisher}.{name} as fx
+
+let echo = fx.process
+    -> delay_ms(350)
+    -> feedback(0.4)
+    -> mix(0.3)
+    -> cutoff(5000)
+```
+
+Each chained parameter calls the matching `set_` export of the plugin:
+
+| Parameter  | Export         | Range          | Default |
+|------------|----------------|----------------|---------|
+| `delay_ms` | `set_delay_ms` | 1 – 2000 ms    | 350     |
+| `feedback` | `set_feedback` | 0.0 – 0.95     | 0.4     |
+| `mix`      | `set_mix`      | 0.0 – 1.0      | 0.3     |
+| `cutoff`   | `set_cutoff`   | 20 – 20000 Hz  | 5000    |
+
+Parameters can be chained in any order; omitted ones keep their current value.
+The delay line keeps its state between blocks, so repeats carry over from one note to the next.
+Call `reset` to clear it.
+"#,
+        publisher = publisher,
+        name = name
+    )
+}
+
+async fn create_plugin_fx_src_lib(rs_path: &Path) -> Result<(), String> {
+    let lib_path = rs_path.join("lib.rs");
+    let src_lib_content: &'static str = r#"// Delay + filter effect plugin using Devalang's safe plugin API
+// The incoming buffer is processed in place.
+//
+// USAGE - Chained parameters:
+//   @use publisher.name as fx
+//   let echo = fx.process
+//       -> delay_ms(350)
+//       -> feedback(0.4)
+//       -> mix(0.3)
+//       -> cutoff(5000)
+//
+// Each parameter calls the matching `set_` export below.
+
+use std::f32::consts::PI;
+use std::sync::{Mutex, OnceLock};
+
+// Longest delay the line can hold
+const MAX_DELAY_MS: f32 = 2000.0;
+
+// Effect state shared across all invocations
+struct FxState {
+    delay_ms: f32,
+    feedback: f32,
+    mix: f32,
+    cutoff: f32,
+    // Interleaved delay line, sized for the host sample rate and channel count
+    line: Vec<f32>,
+    write_pos: usize,
+    sample_rate: u32,
+    channels: usize,
+    // One-pole low-pass memory, one value per channel
+    lowpass: Vec<f32>,
+}
+
+impl FxState {
+    // (Re)allocate the delay line when the host format changes
+    fn prepare(&mut self, sample_rate: u32, channels: usize) {
+        if self.sample_rate == sample_rate && self.channels == channels && !self.line.is_empty() {
+            return;
+        }
+        let frames = (sample_rate as f32 * MAX_DELAY_MS / 1000.0) as usize + 1;
+        self.line = vec![0.0; frames * channels];
+        self.lowpass = vec![0.0; channels];
+        self.write_pos = 0;
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+    }
+}
+
+static STATE: OnceLock<Mutex<FxState>> = OnceLock::new();
+
+fn with_state<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut FxState) -> R
+{
+    let m = STATE.get_or_init(|| Mutex::new(FxState {
+        delay_ms: 350.0,
+        feedback: 0.4,
+        mix: 0.3,
+        cutoff: 5000.0,
+        line: Vec::new(),
+        write_pos: 0,
+        sample_rate: 0,
+        channels: 0,
+        lowpass: Vec::new(),
+    }));
+    let mut g = m.lock().unwrap();
+    f(&mut *g)
+}
+
+// Setter: "delay_ms" - Delay time in milliseconds
+#[unsafe(no_mangle)]
+pub extern "C" fn set_delay_ms(value: f32) {
+    with_state(|state| state.delay_ms = value.clamp(1.0, MAX_DELAY_MS));
+}
+
+// Setter: "feedback" - Amount of the filtered repeat fed back into the line
+#[unsafe(no_mangle)]
+pub extern "C" fn set_feedback(value: f32) {
+    with_state(|state| state.feedback = value.clamp(0.0, 0.95));
+}
+
+// Setter: "mix" - Dry/wet balance (0.0 = dry, 1.0 = wet)
+#[unsafe(no_mangle)]
+pub extern "C" fn set_mix(value: f32) {
+    with_state(|state| state.mix = value.clamp(0.0, 1.0));
+}
+
+// Setter: "cutoff" - Low-pass cutoff of the repeats in Hz
+#[unsafe(no_mangle)]
+pub extern "C" fn set_cutoff(value: f32) {
+    with_state(|state| state.cutoff = value.clamp(20.0, 20000.0));
+}
+
+// Export: "reset" - Clear the delay line and filter memory
+devalang::export_plugin!(reset, |_out, _params, _note, _freq, _amp| {
+    with_state(|state| {
+        state.line.iter_mut().for_each(|s| *s = 0.0);
+        state.lowpass.iter_mut().for_each(|s| *s = 0.0);
+        state.write_pos = 0;
+    });
+});
+
+// Export: "process" - Run the buffer through the delay and filter
+devalang::export_plugin!(process, |out, params, _note, _freq, _amp| {
+    if params.sample_rate == 0 { return; }
+
+    with_state(|state| {
+        let channels = (params.channels as usize).max(1);
+        state.prepare(params.sample_rate, channels);
+
+        let sr = params.sample_rate as f32;
+        let capacity = state.line.len() / channels;
+        // Too short a line for any delay (a very low sample rate): leave the buffer dry
+        if capacity < 2 { return; }
+        let delay = ((state.delay_ms / 1000.0 * sr) as usize).clamp(1, capacity - 1);
+        // y[n] = y[n-1] + a * (x[n] - y[n-1])
+        let a = 1.0 - (-2.0 * PI * state.cutoff / sr).exp();
+
+        for frame in out.chunks_mut(channels) {
+            let read_pos = (state.write_pos + capacity - delay) % capacity;
+            for (ch, sample) in frame.iter_mut().enumerate() {
+                let dry = *sample;
+                let delayed = state.line[read_pos * channels + ch];
+                state.lowpass[ch] += a * (delayed - state.lowpass[ch]);
+                let wet = state.lowpass[ch];
+
+                state.line[state.write_pos * channels + ch] = dry + wet * state.feedback;
+                *sample = dry * (1.0 - state.mix) + wet * state.mix;
+            }
+            state.write_pos = (state.write_pos + 1) % capacity;
+        }
+    });
+});
+"#;
+
+    if let Err(e) = std::fs::write(&lib_path, src_lib_content) {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating lib.rs: {}", e),
+        );
+        return Err(format!("Failed to create lib.rs: {}", e));
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 pub mod empty;
+pub mod fx;
+pub mod sequencer;
 pub mod synth;
-// pub mod fx;
-// pub mod sequencer;
 // pub mod midi;
 // pub mod utility;
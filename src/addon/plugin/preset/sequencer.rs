@@ -0,0 +1,198 @@
+use std::path::Path;
+
+/// Exports declared in the generated plugin.toml.
+///
+/// `export_plugin_with_state!` names its setters `set<Param>` in camel case.
+pub const EXPORTS: &[&str] = &[
+    "sequencer",
+    "setTempo",
+    "setSteps",
+    "setPulses",
+    "setRotate",
+    "setDecay",
+];
+
+/// Extra crates the generated Cargo.toml needs: `export_plugin_with_state!` expands to
+/// `once_cell` and `paste` paths resolved in the plugin crate.
+pub const DEPENDENCIES: &[&str] = &["once_cell = \"1\"", "paste = \"1\""];
+
+pub async fn create_plugin_src_sequencer(src_path: &Path) -> Result<(), String> {
+    if let Err(e) = std::fs::create_dir_all(src_path) {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating plugin src directory: {}", e),
+        );
+        return Err(format!("Failed to create plugin src directory: {}", e));
+    }
+
+    if let Err(e) = create_plugin_sequencer_src_lib(src_path).await {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating plugin src/lib.rs: {}", e),
+        );
+        return Err(format!("Failed to create plugin src/lib.rs: {}", e));
+    }
+
+    Ok(())
+}
+
+/// README section explaining how to drive the sequencer plugin from Devalang.
+///
+/// ### Parameters
+/// - `publisher`: The plugin publisher
+/// - `name`: The plugin name
+///
+pub fn readme_section(publisher: &str, name: &str) -> String {
+    format!(
+        r#"## Usage in Devalang
+
+The `sequencer` export generates a pattern instead of a single tone: a Euclidean rhythm
+spreads `pulses` hits over `steps` sixteenth notes at `tempo` BPM, and every hit plays the
+next note of an arpeggio (root, major third, fifth, octave) built on the incoming note.
+
+```deva
+@use {publisher}.{name} as seq
+
+let arp = seq.sequencer
+    -> tempo(120)
+    -> steps(16)
+    -> pulses(5)
+    -> rotate(0)
+    -> decay(0.15)
+    -> note(A3)
+```
+
+| Parameter | Export      | Range            | Default |
+|-----------|-------------|------------------|---------|
+| `tempo`   | `setTempo`  | 20 – 300 BPM     | 120     |
+| `steps`   | `setSteps`  | 1 – 64           | 16      |
+| `pulses`  | `setPulses` | 0 – `steps`      | 5       |
+| `rotate`  | `setRotate` | 0 – `steps` − 1  | 0       |
+| `decay`   | `setDecay`  | 0.01 – 2.0 s     | 0.15    |
+
+The position in the pattern is kept between renders, so consecutive notes continue the
+sequence instead of restarting it. The state is created on the first render: setter calls
+made before it are ignored and the defaults above apply.
+
+To write your own generator, edit `SeqState` (what is remembered between blocks), the
+render closure (what happens on each sample), and the setters list at the end of the
+`export_plugin_with_state!` call.
+"#,
+        publisher = publisher,
+        name = name
+    )
+}
+
+async fn create_plugin_sequencer_src_lib(rs_path: &Path) -> Result<(), String> {
+    let lib_path = rs_path.join("lib.rs");
+    let src_lib_content: &'static str = r#"// Generative sequencer plugin using Devalang's stateful plugin API
+// A Euclidean rhythm triggers the notes of an arpeggio built on the incoming note.
+//
+// USAGE - Chained parameters:
+//   @use publisher.name as seq
+//   let arp = seq.sequencer
+//       -> tempo(120)
+//       -> steps(16)
+//       -> pulses(5)
+//       -> rotate(0)
+//       -> decay(0.15)
+//       -> note(A3)
+
+use std::f32::consts::TAU;
+
+// Arpeggio intervals in semitones above the incoming note
+const ARPEGGIO: [f32; 4] = [0.0, 4.0, 7.0, 12.0];
+
+devalang::export_plugin_with_state!(
+    sequencer,
+    // Sequencer state kept between renders
+    pub struct SeqState {
+        tempo: f32,
+        steps: u32,
+        pulses: u32,
+        rotate: u32,
+        decay: f32,
+        // Playback position
+        step: u32,
+        sample_in_step: u32,
+        arp_index: usize,
+        // Current voice
+        phase: f32,
+        env: f32,
+        voice_freq: f32,
+    },
+    SeqState {
+        tempo: 120.0,
+        steps: 16,
+        pulses: 5,
+        rotate: 0,
+        decay: 0.15,
+        step: 0,
+        sample_in_step: 0,
+        arp_index: 0,
+        phase: 0.0,
+        env: 0.0,
+        voice_freq: 0.0,
+    },
+    |state: &mut SeqState, out: &mut [f32], params, _note, freq, amp| {
+        let sr = params.sample_rate as f32;
+        let channels = params.channels.max(1) as usize;
+        let base = if freq > 0.0 { freq } else { 220.0 };
+
+        // One step is a sixteenth note
+        let step_len = ((sr * 60.0 / state.tempo) / 4.0).max(1.0) as u32;
+        let release = (-1.0 / (state.decay * sr)).exp();
+
+        for frame in out.chunks_mut(channels) {
+            if state.sample_in_step == 0 && is_hit(state) {
+                let interval = ARPEGGIO[state.arp_index % ARPEGGIO.len()];
+                state.arp_index = (state.arp_index + 1) % ARPEGGIO.len();
+                state.voice_freq = base * 2f32.powf(interval / 12.0);
+                state.phase = 0.0;
+                state.env = 1.0;
+            }
+
+            let sample = (state.phase * TAU).sin() * state.env * amp;
+            state.phase = (state.phase + state.voice_freq / sr).fract();
+            state.env *= release;
+            for s in frame.iter_mut() {
+                *s = sample;
+            }
+
+            state.sample_in_step += 1;
+            if state.sample_in_step >= step_len {
+                state.sample_in_step = 0;
+                state.step = (state.step + 1) % state.steps;
+            }
+        }
+    },
+    {
+        tempo: |state: &mut SeqState, value: f32| state.tempo = value.clamp(20.0, 300.0),
+        steps: |state: &mut SeqState, value: f32| {
+            state.steps = (value as u32).clamp(1, 64);
+            state.pulses = state.pulses.min(state.steps);
+            state.step %= state.steps;
+        },
+        pulses: |state: &mut SeqState, value: f32| state.pulses = (value.max(0.0) as u32).min(state.steps),
+        rotate: |state: &mut SeqState, value: f32| state.rotate = value.max(0.0) as u32 % state.steps,
+        decay: |state: &mut SeqState, value: f32| state.decay = value.clamp(0.01, 2.0),
+    }
+);
+
+// Euclidean rhythm: spreads `pulses` hits as evenly as possible over `steps`
+fn is_hit(state: &SeqState) -> bool {
+    let index = (state.step + state.steps - state.rotate % state.steps) % state.steps;
+    (index * state.pulses) % state.steps < state.pulses
+}
+"#;
+
+    if let Err(e) = std::fs::write(&lib_path, src_lib_content) {
+        crate::utils::logger::Logger::new().log_message(
+            crate::utils::logger::LogLevel::Error,
+            &format!("Error creating lib.rs: {}", e),
+        );
+        return Err(format!("Failed to create lib.rs: {}", e));
+    }
+
+    Ok(())
+}
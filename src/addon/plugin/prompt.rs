@@ -1,56 +1,88 @@
 use crate::utils::logger::{LogLevel, Logger};
 use crate::{
-    addon::plugin::scaffold::scaffold_plugin,
-    utils::{kebab_case::to_kebab_case, spinner::with_spinner},
+    addon::plugin::scaffold::{DevalangDependency, PluginSource, scaffold_plugin},
+    addon::template::{self, TemplateKind},
+    utils::{kebab_case::to_kebab_case, prompt, spinner::with_spinner},
 };
 
-pub async fn prompt_plugin_addon(cwd: &str) -> Result<(), String> {
+/// Built-in presets offered by `plugin create`.
+const PRESETS: [&str; 4] = [
+    "empty",
+    "synth",
+    "fx",
+    "sequencer", // "midi", "utility"
+];
+
+/// Values given on the command line for `plugin create`; missing ones are prompted for.
+#[derive(Debug, Clone, Default)]
+pub struct PluginCreateOptions {
+    pub name: Option<String>,
+    pub publisher: Option<String>,
+    pub description: Option<String>,
+    pub preset: Option<String>,
+    pub template: Option<String>,
+    /// Skip the confirmation prompt
+    pub yes: bool,
+}
+
+/// Prompts the user for plugin details and creates a new plugin.
+///
+/// ### Parameters
+/// - `cwd`: The current directory
+/// - `devalang`: Where the plugin's devalang dependency comes from
+/// - `options`: Values given as flags; only the missing ones are prompted for
+///
+pub async fn prompt_plugin_addon(
+    cwd: &str,
+    devalang: DevalangDependency,
+    options: PluginCreateOptions,
+) -> Result<(), String> {
+    // Fail before prompting when the requested template does not exist
+    let template_source = match options.template {
+        Some(spec) => {
+            let dir = template::resolve_template(cwd, TemplateKind::Plugin, &spec)?;
+            Some((format!("{} (template)", spec), PluginSource::Template(dir)))
+        }
+        None => None,
+    };
+
     println!();
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!("Devalang Plugin Packager");
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!();
 
-    let type_options = vec![
-        "empty", "synth", // "fx", "sequencer", "midi", "utility"
-    ];
-    let final_type =
-        match inquire::Select::new("Enter the plugin preset type:", type_options).prompt() {
-            Ok(type_) => to_kebab_case(type_),
-            Err(e) => {
-                return Err(format!("Failed to prompt for plugin preset type: {}", e));
-            }
-        };
-
-    let final_name = match inquire::Text::new("Enter the plugin name:")
-        .with_default("myplugin")
-        .prompt()
-    {
-        Ok(name) => to_kebab_case(&name).replace("-", ""),
-        Err(e) => {
-            return Err(format!("Failed to prompt for plugin name: {}", e));
-        }
+    let (final_type, final_source) = match template_source {
+        Some(chosen) => chosen,
+        None => prompt_plugin_source(cwd, options.preset)?,
     };
 
-    let final_publisher = match inquire::Text::new("Enter the plugin publisher:")
-        .with_default("johndoe")
-        .prompt()
-    {
-        Ok(publisher) => to_kebab_case(&publisher),
-        Err(e) => {
-            return Err(format!("Failed to prompt for plugin publisher: {}", e));
-        }
-    };
+    let final_name = prompt::text(
+        options.name,
+        "name",
+        "Enter the plugin name:",
+        "myplugin",
+        true,
+    )
+    .map(|name| to_kebab_case(&name).replace("-", ""))?;
 
-    let final_description = match inquire::Text::new("Enter the plugin description:")
-        .with_default("A description of my plugin")
-        .prompt()
-    {
-        Ok(description) => to_kebab_case(&description),
-        Err(e) => {
-            return Err(format!("Failed to prompt for plugin description: {}", e));
-        }
-    };
+    let final_publisher = prompt::text(
+        options.publisher,
+        "publisher",
+        "Enter the plugin publisher:",
+        "johndoe",
+        true,
+    )
+    .map(|publisher| to_kebab_case(&publisher))?;
+
+    let final_description = prompt::text(
+        options.description,
+        "description",
+        "Enter the plugin description:",
+        "A description of my plugin",
+        false,
+    )
+    .map(|description| to_kebab_case(&description))?;
 
     // TODO Enable this when we support private/protected plugins
     // let options = vec!["public", "private", "protected"];
@@ -87,12 +119,8 @@ pub async fn prompt_plugin_addon(cwd: &str) -> Result<(), String> {
 
     println!();
 
-    let confirm_prompt = inquire::Confirm::new("Are these details correct ?")
-        .with_default(true)
-        .prompt();
-
-    match confirm_prompt {
-        Ok(true) => {
+    match prompt::confirm(options.yes, "Are these details correct ?")? {
+        true => {
             let spinner = with_spinner("Generating plugin...");
 
             let res = scaffold_plugin(
@@ -101,15 +129,67 @@ pub async fn prompt_plugin_addon(cwd: &str) -> Result<(), String> {
                 final_publisher,
                 final_description,
                 final_access,
-                final_type,
+                final_source,
+                &devalang,
             )
             .await;
             spinner.finish_and_clear();
             res
         }
-        _ => {
+        false => {
             Logger::new().log_message(LogLevel::Warning, "Aborting plugin scaffolding.");
             Err("aborted by user".into())
         }
     }
 }
+
+/// Asks for a built-in preset or one of the discovered user templates.
+///
+/// Uses `--preset` when given, and fails when stdin is not a terminal.
+///
+/// ### Parameters
+/// - `cwd`: The current directory
+/// - `preset`: The `--preset` value, if given
+///
+fn prompt_plugin_source(
+    cwd: &str,
+    preset: Option<String>,
+) -> Result<(String, PluginSource), String> {
+    if let Some(p) = preset {
+        if !PRESETS.contains(&p.as_str()) {
+            return Err(format!(
+                "Invalid --preset '{}': expected one of {}",
+                p,
+                PRESETS.join(", ")
+            ));
+        }
+        return Ok((p.clone(), PluginSource::Preset(p)));
+    }
+    if !prompt::is_interactive() {
+        return Err(format!(
+            "Missing --preset (one of {}) or --template: stdin is not a terminal, so it cannot be prompted for",
+            PRESETS.join(", ")
+        ));
+    }
+
+    let templates = template::discover_templates(cwd, TemplateKind::Plugin);
+
+    let mut type_options: Vec<String> = PRESETS.iter().map(|p| p.to_string()).collect();
+    type_options.extend(templates.iter().map(|t| format!("{} (template)", t.name)));
+
+    let choice =
+        match inquire::Select::new("Enter the plugin preset type:", type_options).raw_prompt() {
+            Ok(choice) => choice,
+            Err(e) => {
+                return Err(format!("Failed to prompt for plugin preset type: {}", e));
+            }
+        };
+
+    if choice.index < PRESETS.len() {
+        let preset = to_kebab_case(&choice.value);
+        Ok((preset.clone(), PluginSource::Preset(preset)))
+    } else {
+        let t = &templates[choice.index - PRESETS.len()];
+        Ok((choice.value, PluginSource::Template(t.path.clone())))
+    }
+}
@@ -1,19 +1,36 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::addon::plugin::preset::{
-    empty::create_plugin_src_empty, synth::create_plugin_src_synth,
+    empty::create_plugin_src_empty, fx, fx::create_plugin_src_fx, sequencer,
+    sequencer::create_plugin_src_sequencer, synth::create_plugin_src_synth,
+};
+use crate::addon::plugin::workspace;
+use crate::addon::template;
+use crate::builder::readme;
+use crate::utils::{
+    config, fs as ufs,
+    logger::{LogLevel, Logger},
+    version::get_version,
 };
-use crate::utils::logger::{LogLevel, Logger};
-use reqwest;
 use serde_json::Value as JsonValue;
 
+/// What a new plugin is generated from.
+#[derive(Debug, Clone)]
+pub enum PluginSource {
+    /// One of the built-in presets (`empty`, `synth`, `fx`, `sequencer`)
+    Preset(String),
+    /// A user template directory; files it lacks are generated as for the `empty` preset
+    Template(PathBuf),
+}
+
 pub async fn scaffold_plugin(
     cwd: &str,
     name: String,
     publisher: String,
     description: String,
     access: String,
-    preset_type: String,
+    source: PluginSource,
+    devalang: &DevalangDependency,
 ) -> Result<(), String> {
     let plugins_root = Path::new(cwd).join("generated").join("plugins");
 
@@ -31,7 +48,35 @@ pub async fn scaffold_plugin(
         return Err(format!("Failed to create plugin directory: {}", e));
     }
 
-    if let Err(e) = create_plugin_toml(&plugin_path, &name, &publisher, &description, &access).await
+    let preset_type = match &source {
+        PluginSource::Preset(preset) => preset.clone(),
+        PluginSource::Template(dir) => {
+            let vars = [
+                ("name", name.as_str()),
+                ("publisher", publisher.as_str()),
+                ("description", description.as_str()),
+            ];
+            if let Err(e) = template::copy_template(dir, &plugin_path, &vars) {
+                Logger::new().log_message(
+                    LogLevel::Error,
+                    &format!("Error copying template {}: {}", dir.display(), e),
+                );
+                return Err(format!("Failed to copy template: {}", e));
+            }
+            "empty".to_string()
+        }
+    };
+
+    if !plugin_path.join("plugin.toml").exists()
+        && let Err(e) = create_plugin_toml(
+            &plugin_path,
+            &name,
+            &publisher,
+            &description,
+            &access,
+            preset_exports(&preset_type),
+        )
+        .await
     {
         Logger::new().log_message(
             LogLevel::Error,
@@ -40,8 +85,25 @@ pub async fn scaffold_plugin(
         return Err(format!("Failed to create plugin toml: {}", e));
     }
 
-    if let Err(e) =
-        create_plugin_cargo_toml(cwd, &plugin_path, &name, &publisher, &description).await
+    if plugin_path.join("Cargo.toml").exists() {
+        // Templates ship their own manifest; it only needs registering.
+        if let Err(e) = workspace::register_plugin(cwd, &publisher, &name) {
+            Logger::new().log_message(
+                LogLevel::Error,
+                &format!("Error adding plugin to workspace: {}", e),
+            );
+            return Err(format!("Failed to add plugin to workspace: {}", e));
+        }
+    } else if let Err(e) = create_plugin_cargo_toml(
+        cwd,
+        &plugin_path,
+        &name,
+        &publisher,
+        &description,
+        devalang,
+        preset_dependencies(&preset_type),
+    )
+    .await
     {
         Logger::new().log_message(
             LogLevel::Error,
@@ -50,7 +112,9 @@ pub async fn scaffold_plugin(
         return Err(format!("Failed to create Cargo.toml: {}", e));
     }
 
-    if let Err(e) = create_plugin_src_dir(&plugin_path, &preset_type).await {
+    if !plugin_path.join("src").join("lib.rs").exists()
+        && let Err(e) = create_plugin_src_dir(&plugin_path, &preset_type).await
+    {
         Logger::new().log_message(
             LogLevel::Error,
             &format!("Error creating plugin src directory: {}", e),
@@ -58,7 +122,9 @@ pub async fn scaffold_plugin(
         return Err(format!("Failed to create plugin src directory: {}", e));
     }
 
-    if let Err(e) = write_default_docs(&plugin_path, &publisher, &name, &description).await {
+    if let Err(e) =
+        write_default_docs(&plugin_path, &publisher, &name, &description, &preset_type).await
+    {
         Logger::new().log_message(
             LogLevel::Warning,
             &format!("Warning: failed to create default docs: {}", e),
@@ -73,14 +139,24 @@ async fn write_default_docs(
     publisher: &str,
     name: &str,
     description: &str,
+    preset_type: &str,
 ) -> Result<(), String> {
     // README.md
     let readme_path = plugin_path.join("README.md");
     if !readme_path.exists() {
-        let readme = format!(
-            "# {}.{} Plugin\n\n{}\n\nContents:\n- plugin.toml\n- src/lib.rs\n- LICENSE\n\nBuilt with devapack.\n",
-            publisher, name, description
+        // The marked block is filled with the exports and parameters on every build
+        let mut readme = format!(
+            "# {}.{} Plugin\n\n{}\n\nContents:\n- plugin.toml\n- src/lib.rs\n- LICENSE\n\nBuilt with devapack.\n\n{}",
+            publisher,
+            name,
+            description,
+            readme::generated_block("_Run `devapack plugin build` to list the exports here._")
         );
+        // Presets with parameters document how to use them
+        if let Some(section) = preset_readme_section(preset_type, publisher, name) {
+            readme.push('\n');
+            readme.push_str(&section);
+        }
         std::fs::write(&readme_path, readme)
             .map_err(|e| format!("Failed to write README.md: {}", e))?;
     }
@@ -99,25 +175,48 @@ async fn write_default_docs(
     Ok(())
 }
 
+/// Exports a preset declares in plugin.toml; the builder refreshes them from the compiled module.
+fn preset_exports(preset_type: &str) -> &'static [&'static str] {
+    match preset_type {
+        "fx" => fx::EXPORTS,
+        "sequencer" => sequencer::EXPORTS,
+        _ => &["process"],
+    }
+}
+
+/// Cargo dependencies a preset needs on top of `devalang`.
+fn preset_dependencies(preset_type: &str) -> &'static [&'static str] {
+    match preset_type {
+        "sequencer" => sequencer::DEPENDENCIES,
+        _ => &[],
+    }
+}
+
+/// README section documenting a preset's parameters, if it has any.
+fn preset_readme_section(preset_type: &str, publisher: &str, name: &str) -> Option<String> {
+    match preset_type {
+        "fx" => Some(fx::readme_section(publisher, name)),
+        "sequencer" => Some(sequencer::readme_section(publisher, name)),
+        _ => None,
+    }
+}
+
 pub async fn create_plugin_toml(
     plugin_path: &Path,
     name: &str,
     publisher: &str,
     description: &str,
     access: &str,
+    exports: &[&str],
 ) -> Result<(), String> {
     let version = "0.0.1";
-    let toml_content = format!(
+    let mut toml_content = format!(
         r#"[plugin]
 name = "{name}"
 publisher = "{publisher}"
 description = "{description}"
 version = "{version}"
 access = "{access}"
-
-[[exports]]
-name = "process"
-kind = "func"
 "#,
         name = name,
         publisher = publisher,
@@ -125,6 +224,12 @@ kind = "func"
         version = version,
         access = access
     );
+    for export in exports {
+        toml_content.push_str(&format!(
+            "\n[[exports]]\nname = \"{}\"\nkind = \"func\"\n",
+            export
+        ));
+    }
 
     let toml_path = plugin_path.join("plugin.toml");
     if let Err(e) = std::fs::write(&toml_path, toml_content) {
@@ -138,102 +243,82 @@ kind = "func"
     Ok(())
 }
 
+/// Where the generated plugin's `devalang` dependency comes from.
+///
+/// Flags win over the user config (`"devapack"` in `~/.devalang/config.json`), whichever of
+/// path or version it sets, and the config wins over the crates.io lookup. The last version
+/// seen on crates.io is cached so scaffolding keeps working offline.
+#[derive(Debug, Clone, Default)]
+pub struct DevalangDependency {
+    /// Exact crates.io version to depend on (e.g. `0.2.0`)
+    pub version: Option<String>,
+    /// Local checkout of the devalang crate, relative to the project root or absolute
+    pub path: Option<String>,
+    /// Skip the crates.io lookup and use the cached version
+    pub offline: bool,
+}
+
+enum DevalangSource {
+    Version(String),
+    Path(String),
+}
+
+const DEVALANG_VERSION_CACHE: &str = "devalang-version";
+
 pub async fn create_plugin_cargo_toml(
     cwd: &str,
     plugin_path: &Path,
     name: &str,
     publisher: &str,
     description: &str,
+    devalang: &DevalangDependency,
+    extra_dependencies: &[&str],
 ) -> Result<(), String> {
-    // Helper: attempt to fetch latest version of a crate from crates.io
-    async fn latest_crate_version(crate_name: &str) -> Result<Option<String>, String> {
-        let url = format!("https://crates.io/api/v1/crates/{}", crate_name);
-        let resp = reqwest::get(&url)
-            .await
-            .map_err(|e| format!("Failed to query crates.io: {}", e))?;
-        if !resp.status().is_success() {
-            return Ok(None);
-        }
-        let json: JsonValue = resp
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse crates.io response: {}", e))?;
-        if let Some(v) = json
-            .get("crate")
-            .and_then(|c| c.get("max_version"))
-            .and_then(|m| m.as_str())
-        {
-            Ok(Some(v.to_string()))
-        } else {
-            Ok(None)
-        }
+    let mut dependency = match resolve_devalang_source(cwd, devalang).await? {
+        DevalangSource::Version(ver) => format!(
+            r#"devalang = {{ package = "devalang", version = "={ver}", default-features = false, features = ["plugin"] }}"#,
+            ver = ver
+        ),
+        DevalangSource::Path(path) => format!(
+            r#"devalang = {{ package = "devalang", path = "{path}", default-features = false, features = ["plugin"] }}"#,
+            path = path
+        ),
+    };
+
+    for extra in extra_dependencies {
+        dependency.push('\n');
+        dependency.push_str(extra);
     }
 
-    // Try to get the latest published version of `devalang` from crates.io.
-    // If we can fetch it, generate the plugin Cargo.toml to depend on that version.
-    // Otherwise, fall back to using the local relative path to `devalang`.
-    let registry_version = match latest_crate_version("devalang").await {
-        Ok(Some(v)) => {
-            Logger::new().log_message(
-                LogLevel::Info,
-                &format!(
-                    "Using devalang crate version {} from crates.io for plugin Cargo.toml",
-                    v
-                ),
-            );
-            Some(v)
-        }
-        Ok(None) => {
-            Logger::new().log_message(
-                LogLevel::Warning,
-                "Could not find devalang on crates.io, falling back to local path dependency.",
-            );
-            None
-        }
-        Err(e) => {
-            Logger::new().log_message(
-                LogLevel::Warning,
-                &format!(
-                    "Failed to query crates.io for devalang: {}. Using local path.",
-                    e
-                ),
-            );
-            None
-        }
+    // Inside the shared plugins workspace the crate must not declare its own workspace.
+    let workspace_section = if workspace::plugins_workspace_enabled(cwd) {
+        ""
+    } else {
+        "[workspace]\nmembers = [\".\"]\n\n"
     };
 
-    let cargo_toml_content = if let Some(ver) = registry_version {
-        format!(
-            r#"[package]
+    let cargo_toml_content = format!(
+        r#"[package]
 name = "{name}"
 description = "{description}"
 version = "0.0.1"
 authors = ['{publisher}']
 edition = "2024"
 
-[workspace]
-members = ["."]
-
-[lib]
+{workspace_section}[lib]
 name = "{name}"
 path = "src/lib.rs"
 crate-type = ["cdylib"]
 
 [dependencies]
-devalang = {{ version = "{ver}", default-features = false, features = ["plugin"] }}
+{dependency}
 "#,
-            name = name,
-            description = description,
-            publisher = publisher,
-            ver = ver
-        )
-    } else {
-        Logger::new().log_message(
-            LogLevel::Error,
-            &format!("Unable to determine Devalang version from crates.io"),
-        );
-        return Err("Unable to determine Devalang version from crates.io".into());
-    };
+        name = name,
+        description = description,
+        publisher = publisher,
+        workspace_section = workspace_section,
+        dependency = dependency
+    );
 
     // Write plugin Cargo.toml
     let cargo_toml_path = plugin_path.join("Cargo.toml");
@@ -245,7 +330,7 @@ devalang = {{ version = "{ver}", default-features = false, features = ["plugin"]
         return Err(format!("Failed to create Cargo.toml: {}", e));
     }
 
-    if let Err(e) = add_plugin_to_root_cargo(cwd).await {
+    if let Err(e) = workspace::register_plugin(cwd, publisher, name) {
         Logger::new().log_message(
             LogLevel::Error,
             &format!("Error adding plugin to workspace: {}", e),
@@ -256,142 +341,171 @@ devalang = {{ version = "{ver}", default-features = false, features = ["plugin"]
     Ok(())
 }
 
-pub async fn add_plugin_to_root_cargo(cwd: &str) -> Result<(), String> {
-    let cargo_toml_root_path = Path::new(cwd).join("Cargo.toml");
-
-    // find most recently modified plugin directory under generated/plugins/<publisher>/<name>
-    let generated_dir = Path::new(cwd).join("generated").join("plugins");
-    let mut newest: Option<(std::time::SystemTime, String)> = None;
-    if let Ok(rd) = std::fs::read_dir(&generated_dir) {
-        for pub_entry in rd.flatten() {
-            if let Ok(pub_ft) = pub_entry.file_type() {
-                if pub_ft.is_dir() {
-                    let pub_path = pub_entry.path();
-                    if let Ok(child_rd) = std::fs::read_dir(&pub_path) {
-                        for child in child_rd.flatten() {
-                            if let Ok(child_ft) = child.file_type() {
-                                if child_ft.is_dir() {
-                                    if let Ok(m) = child.metadata().and_then(|m| m.modified()) {
-                                        if let (Some(pub_name), Some(child_name)) = (
-                                            pub_entry.file_name().to_str(),
-                                            child.file_name().to_str(),
-                                        ) {
-                                            let rel = format!(
-                                                "generated/plugins/{}/{}",
-                                                pub_name, child_name
-                                            );
-                                            if newest.as_ref().map(|(t, _)| m > *t).unwrap_or(true)
-                                            {
-                                                newest = Some((m, rel));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+/// Picks the devalang dependency: the flags (path, then version), then the user config
+/// (path, then version), then crates.io, then the cache.
+///
+/// ### Parameters
+/// - `cwd`: The project root, used to resolve relative local paths
+/// - `devalang`: Options given on the command line
+///
+async fn resolve_devalang_source(
+    cwd: &str,
+    devalang: &DevalangDependency,
+) -> Result<DevalangSource, String> {
+    if let Some(p) = &devalang.path {
+        return local_devalang_path(cwd, p).map(DevalangSource::Path);
+    }
+    if let Some(v) = &devalang.version {
+        return pinned_devalang_version(v, "--devalang-version");
+    }
+    if let Some(p) = config::devapack_setting("devalang_path") {
+        return local_devalang_path(cwd, &p).map(DevalangSource::Path);
+    }
+    if let Some(v) = config::devapack_setting("devalang_version") {
+        return pinned_devalang_version(&v, "devapack.devalang_version");
+    }
+
+    if !devalang.offline {
+        match latest_crate_version("devalang").await {
+            Ok(Some(v)) => {
+                Logger::new().log_message(
+                    LogLevel::Info,
+                    &format!(
+                        "Using devalang crate version {} from crates.io for plugin Cargo.toml",
+                        v
+                    ),
+                );
+                if let Err(e) = write_cached_devalang_version(&v) {
+                    Logger::new().log_message(
+                        LogLevel::Warning,
+                        &format!("Failed to cache devalang version: {}", e),
+                    );
                 }
+                return Ok(DevalangSource::Version(v));
             }
+            Ok(None) => Logger::new().log_message(
+                LogLevel::Warning,
+                "Could not find devalang on crates.io, falling back to the cached version.",
+            ),
+            Err(e) => Logger::new().log_message(
+                LogLevel::Warning,
+                &format!(
+                    "Failed to query crates.io for devalang: {}. Falling back to the cached version.",
+                    e
+                ),
+            ),
         }
     }
-    let plugin = match newest.map(|(_, p)| p) {
-        Some(p) => p,
-        None => {
-            return Ok(());
+
+    match read_cached_devalang_version() {
+        Some(v) => {
+            Logger::new().log_message(
+                LogLevel::Info,
+                &format!("Using cached devalang version {}", v),
+            );
+            Ok(DevalangSource::Version(v))
         }
-    };
+        None => Err(
+            "Unable to determine the devalang version: no network and no cached version. \
+             Pass --devalang-version <x.y.z> or --devalang-path <dir>, or set \
+             devapack.devalang_version in ~/.devalang/config.json"
+                .into(),
+        ),
+    }
+}
 
-    let orig = std::fs::read_to_string(&cargo_toml_root_path)
-        .map_err(|e| format!("Failed to read root Cargo.toml: {}", e))?;
-
-    // ensure [workspace] exists with members = ["."]
-    let mut out = orig.clone();
-    if !orig.contains("[workspace]") {
-        out.push_str("\n[workspace]\nmembers = [\".\"]\nexclude = [\"");
-        out.push_str(&plugin);
-        out.push_str("\"]\n");
-        std::fs::write(&cargo_toml_root_path, out)
-            .map_err(|e| format!("Failed to write root Cargo.toml: {}", e))?;
-        return Ok(());
+/// Queries crates.io for the latest published version of a crate.
+async fn latest_crate_version(crate_name: &str) -> Result<Option<String>, String> {
+    let url = format!("https://crates.io/api/v1/crates/{}", crate_name);
+    // crates.io rejects requests without a User-Agent
+    let client = reqwest::Client::builder()
+        .user_agent(format!("devapack/{}", get_version()))
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let resp = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query crates.io: {}", e))?;
+    if !resp.status().is_success() {
+        return Ok(None);
     }
+    let json: JsonValue = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse crates.io response: {}", e))?;
+    Ok(json
+        .get("crate")
+        .and_then(|c| c.get("max_version"))
+        .and_then(|m| m.as_str())
+        .map(|v| v.to_string()))
+}
 
-    // operate inside workspace section
-    let lines: Vec<&str> = orig.lines().collect();
-    let mut start = None;
-    for (i, l) in lines.iter().enumerate() {
-        if l.trim() == "[workspace]" {
-            start = Some(i);
-            break;
-        }
+/// A pinned devalang version, checked to make a valid Cargo requirement (`=<version>`)
+/// before it is written into Cargo.toml.
+///
+/// ### Parameters
+/// - `version`: The pinned version
+/// - `origin`: Where it was set, for the error message
+///
+fn pinned_devalang_version(version: &str, origin: &str) -> Result<DevalangSource, String> {
+    let v = version.trim().trim_start_matches('=').to_string();
+    semver::VersionReq::parse(&format!("={}", v))
+        .map_err(|e| format!("Invalid devalang version '{}' ({}): {}", version, origin, e))?;
+    Logger::new().log_message(LogLevel::Info, &format!("Using pinned devalang {}", v));
+    Ok(DevalangSource::Version(v))
+}
+
+fn read_cached_devalang_version() -> Option<String> {
+    let path = config::cache_dir().ok()?.join(DEVALANG_VERSION_CACHE);
+    let v = std::fs::read_to_string(path).ok()?;
+    let v = v.trim();
+    if v.is_empty() {
+        None
+    } else {
+        Some(v.to_string())
     }
-    let s = match start {
-        Some(s) => s,
-        None => {
-            return Ok(());
-        }
+}
+
+fn write_cached_devalang_version(version: &str) -> Result<(), String> {
+    let dir = config::cache_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cache dir: {}", e))?;
+    std::fs::write(dir.join(DEVALANG_VERSION_CACHE), version)
+        .map_err(|e| format!("Failed to write cache: {}", e))
+}
+
+/// Validates a local devalang checkout and returns the path as seen from the plugin crate.
+///
+/// Relative paths are taken from the project root and rewritten relative to
+/// `generated/plugins/<publisher>/<name>` so the scaffold stays portable across machines.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `path`: The devalang checkout (absolute, or relative to `cwd`)
+///
+fn local_devalang_path(cwd: &str, path: &str) -> Result<String, String> {
+    let given = Path::new(path);
+    let from_root = if given.is_absolute() {
+        given.to_path_buf()
+    } else {
+        Path::new(cwd).join(given)
     };
-    let mut end = lines.len();
-    for (i, _) in lines.iter().enumerate().skip(s + 1) {
-        if lines[i].trim_start().starts_with('[') {
-            end = i;
-            break;
-        }
+    if !from_root.join("Cargo.toml").is_file() {
+        return Err(format!(
+            "No Cargo.toml found in devalang path {}",
+            from_root.display()
+        ));
     }
-    let section = lines[s..end].join("\n");
-
-    if section.contains("exclude") {
-        // find first '[' and ']' after exclude
-        if let Some(p) = section.find("exclude") {
-            if let Some(o) = section[p..].find('[') {
-                let open = p + o;
-                if let Some(c) = section[open..].find(']') {
-                    let close = open + c;
-                    let inside = &section[open + 1..close];
-                    let mut items: Vec<String> = inside
-                        .split(',')
-                        .map(|s| s.trim().trim_matches('"').to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-                    if items.iter().any(|it| it == &plugin) {
-                        return Ok(());
-                    }
-                    items.push(plugin.clone());
-                    let new_inside = items
-                        .into_iter()
-                        .map(|it| format!("\"{}\"", it))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    let old_fragment = &section[open..=close];
-                    let new_fragment = format!("[{}]", new_inside);
-                    let new_section = section.replacen(old_fragment, &new_fragment, 1);
-                    out = orig.replacen(&section, &new_section, 1);
-                    std::fs::write(&cargo_toml_root_path, out)
-                        .map_err(|e| format!("Failed to write root Cargo.toml: {}", e))?;
-                    return Ok(());
-                }
-            }
-        }
+    Logger::new().log_message(
+        LogLevel::Info,
+        &format!("Using local devalang at {}", from_root.display()),
+    );
+    if given.is_absolute() {
+        Ok(ufs::to_unix_string(given))
     } else {
-        // insert exclude = ["plugin"] after members line if present, else after header
-        let mut new_lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
-        let mut inserted = false;
-        for i in s + 1..end {
-            if new_lines[i].contains("members") {
-                new_lines.insert(i + 1, format!("exclude = [\"{}\"]", plugin));
-                inserted = true;
-                break;
-            }
-        }
-        if !inserted {
-            new_lines.insert(s + 1, format!("exclude = [\"{}\"]", plugin));
-        }
-        out = new_lines.join("\n");
-        std::fs::write(&cargo_toml_root_path, out)
-            .map_err(|e| format!("Failed to write root Cargo.toml: {}", e))?;
-        return Ok(());
+        Ok(format!("../../../../{}", ufs::to_unix_string(given)))
     }
-
-    Ok(())
 }
 
 pub async fn create_plugin_src_dir(plugin_path: &Path, preset_type: &str) -> Result<(), String> {
@@ -418,13 +532,25 @@ pub async fn create_plugin_src_dir(plugin_path: &Path, preset_type: &str) -> Res
             }
         }
 
-        // "fx" => {
-        //     // Create an effects plugin structure
-        // }
+        "fx" => {
+            if let Err(e) = create_plugin_src_fx(&src_path).await {
+                Logger::new().log_message(
+                    LogLevel::Error,
+                    &format!("Error creating fx plugin src: {}", e),
+                );
+                return Err(format!("Failed to create fx plugin src: {}", e));
+            }
+        }
 
-        // "sequencer" => {
-        //     // Create a sequencer plugin structure
-        // }
+        "sequencer" => {
+            if let Err(e) = create_plugin_src_sequencer(&src_path).await {
+                Logger::new().log_message(
+                    LogLevel::Error,
+                    &format!("Error creating sequencer plugin src: {}", e),
+                );
+                return Err(format!("Failed to create sequencer plugin src: {}", e));
+            }
+        }
 
         // "midi" => {
         //     // Create a MIDI plugin structure
@@ -0,0 +1,559 @@
+use crate::utils::logger::{LogLevel, Logger};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+
+/// Path of generated plugins relative to the project root.
+pub const PLUGINS_DIR: &str = "generated/plugins";
+
+/// Reads and parses a Cargo.toml, keeping its formatting and comments for writing back.
+fn read_manifest(path: &Path) -> Result<DocumentMut, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_manifest(&text, path)
+}
+
+fn parse_manifest(text: &str, path: &Path) -> Result<DocumentMut, String> {
+    text.parse::<DocumentMut>()
+        .map_err(|e| format!("Invalid TOML in {}: {}", path.display(), e))
+}
+
+fn write_manifest(path: &Path, doc: &DocumentMut) -> Result<(), String> {
+    fs::write(path, doc.to_string())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The `[workspace]` table of a manifest, created at the end of it when missing.
+fn workspace_table(doc: &mut DocumentMut) -> Result<&mut Table, String> {
+    if doc.get("workspace").is_none() {
+        let mut table = Table::new();
+        if !doc.as_table().is_empty() {
+            table.decor_mut().set_prefix("\n");
+        }
+        doc.insert("workspace", Item::Table(table));
+    }
+    let table = doc["workspace"]
+        .as_table_mut()
+        .ok_or("`workspace` is not a table")?;
+    // a section only known through its `[workspace.*]` subsections gets its own header
+    table.set_implicit(false);
+    Ok(table)
+}
+
+/// The string array `key` of `[workspace]`, if the manifest has one.
+fn workspace_array<'a>(doc: &'a mut DocumentMut, key: &str) -> Option<&'a mut Array> {
+    doc.get_mut("workspace")?
+        .as_table_mut()?
+        .get_mut(key)?
+        .as_array_mut()
+}
+
+fn same_path(item: &Value, value: &str) -> bool {
+    item.as_str()
+        .is_some_and(|it| it.trim_end_matches('/') == value.trim_end_matches('/'))
+}
+
+/// Appends an item, laid out like the items before it: on its own line in an array that
+/// lists one item per line.
+fn push_item(array: &mut Array, value: &str) {
+    let multiline = array.trailing().as_str().unwrap_or("").contains('\n');
+    let indent = match array.iter().last() {
+        Some(last) => last
+            .decor()
+            .prefix()
+            .and_then(|p| p.as_str())
+            .and_then(|p| p.rfind('\n').map(|i| p[i..].to_string())),
+        None if multiline => Some("\n    ".to_string()),
+        None => None,
+    };
+    // a comment after the last item has to stay after its comma, so it moves to the new item
+    let mut comment = String::new();
+    if let Some(last) = array.iter_mut().last()
+        && let Some(suffix) = last.decor().suffix().and_then(|s| s.as_str())
+        && suffix.contains('#')
+    {
+        comment = suffix.trim_end().to_string();
+        last.decor_mut().set_suffix("");
+    }
+    array.push(value);
+    if let (Some(indent), Some(item)) = (indent, array.iter_mut().last()) {
+        item.decor_mut()
+            .set_prefix(format!("{}{}", comment, indent));
+        array.set_trailing_comma(true);
+        if !multiline {
+            array.set_trailing("\n");
+        }
+    }
+}
+
+/// Adds `value` to `[workspace].<key>`, creating the section or key when missing.
+///
+/// Returns `false` when the value is already listed.
+///
+/// ### Parameters
+/// - `doc`: The manifest
+/// - `key`: The array key, e.g. `exclude`
+/// - `value`: The entry to add
+///
+fn add_to_array(doc: &mut DocumentMut, key: &str, value: &str) -> Result<bool, String> {
+    let table = workspace_table(doc)?;
+    if table.get(key).is_none() {
+        table.insert(key, toml_edit::value(Array::new()));
+    }
+    let array = table[key]
+        .as_array_mut()
+        .ok_or_else(|| format!("`workspace.{}` is not an array", key))?;
+    if array.iter().any(|it| same_path(it, value)) {
+        return Ok(false);
+    }
+    push_item(array, value);
+    Ok(true)
+}
+
+/// Removes `value` from `[workspace].<key>`. Returns `false` when it was not listed.
+fn remove_from_array(doc: &mut DocumentMut, key: &str, value: &str) -> bool {
+    let Some(array) = workspace_array(doc, key) else {
+        return false;
+    };
+    let before = array.len();
+    array.retain(|it| !same_path(it, value));
+    array.len() != before
+}
+
+/// The Cargo resolver for a workspace of crates on the given editions: `3` when they are
+/// all on edition 2024, which requires it, and `2` otherwise.
+fn resolver_for(editions: &[Option<String>]) -> &'static str {
+    if !editions.is_empty() && editions.iter().all(|e| e.as_deref() == Some("2024")) {
+        "3"
+    } else {
+        "2"
+    }
+}
+
+/// Path of the shared plugins workspace manifest, `generated/plugins/Cargo.toml`.
+pub fn plugins_workspace_manifest(cwd: &str) -> PathBuf {
+    Path::new(cwd).join(PLUGINS_DIR).join("Cargo.toml")
+}
+
+/// Whether plugins are managed as members of the shared `generated/plugins` workspace.
+pub fn plugins_workspace_enabled(cwd: &str) -> bool {
+    plugins_workspace_manifest(cwd).is_file()
+}
+
+/// Registers a freshly scaffolded plugin.
+///
+/// With the shared plugins workspace the plugin becomes a member of
+/// `generated/plugins/Cargo.toml` and any `[workspace]` section of its own manifest (e.g. from
+/// a template) is removed; otherwise its own path is excluded from the root workspace.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `publisher`: The plugin publisher
+/// - `name`: The plugin name
+///
+pub fn register_plugin(cwd: &str, publisher: &str, name: &str) -> Result<(), String> {
+    if plugins_workspace_enabled(cwd) {
+        let manifest = plugins_workspace_manifest(cwd);
+        let mut doc = read_manifest(&manifest)?;
+        if add_to_array(&mut doc, "members", &format!("{}/{}", publisher, name))? {
+            write_manifest(&manifest, &doc)?;
+        }
+        let plugin_manifest = Path::new(cwd)
+            .join(PLUGINS_DIR)
+            .join(publisher)
+            .join(name)
+            .join("Cargo.toml");
+        if plugin_manifest.is_file() {
+            let mut plugin_doc = read_manifest(&plugin_manifest)?;
+            if strip_workspace_section(&mut plugin_doc) {
+                write_manifest(&plugin_manifest, &plugin_doc)?;
+            }
+        }
+        // the root workspace only needs to skip the plugins workspace as a whole
+        return add_plugin_to_root_cargo(cwd, PLUGINS_DIR);
+    }
+    add_plugin_to_root_cargo(cwd, &format!("{}/{}/{}", PLUGINS_DIR, publisher, name))
+}
+
+/// Removes a plugin from the shared plugins workspace and the root exclude list.
+///
+/// Returns `true` when any manifest was changed.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `publisher`: The plugin publisher
+/// - `name`: The plugin name
+///
+pub fn unregister_plugin(cwd: &str, publisher: &str, name: &str) -> Result<bool, String> {
+    let mut changed = false;
+    if plugins_workspace_enabled(cwd) {
+        let manifest = plugins_workspace_manifest(cwd);
+        let mut doc = read_manifest(&manifest)?;
+        if remove_from_array(&mut doc, "members", &format!("{}/{}", publisher, name)) {
+            write_manifest(&manifest, &doc)?;
+            changed = true;
+        }
+    }
+    let rel = format!("{}/{}/{}", PLUGINS_DIR, publisher, name);
+    Ok(remove_plugin_from_root_cargo(cwd, &rel)? || changed)
+}
+
+/// Adds a path to the root Cargo.toml `[workspace].exclude` list.
+///
+/// Does nothing when the project has no root Cargo.toml.
+///
+/// ### Parameters
+/// - `cwd`: The project root containing Cargo.toml
+/// - `plugin_rel`: The path to exclude, e.g. `generated/plugins/<publisher>/<name>`
+///
+pub fn add_plugin_to_root_cargo(cwd: &str, plugin_rel: &str) -> Result<(), String> {
+    let cargo_toml_root_path = Path::new(cwd).join("Cargo.toml");
+    if !cargo_toml_root_path.exists() {
+        return Ok(());
+    }
+    let mut doc = read_manifest(&cargo_toml_root_path)?;
+    let mut changed = false;
+    if doc.get("workspace").is_none() {
+        // a root workspace that only contains the root crate
+        changed |= add_to_array(&mut doc, "members", ".")?;
+    }
+    changed |= add_to_array(&mut doc, "exclude", plugin_rel)?;
+    if changed {
+        write_manifest(&cargo_toml_root_path, &doc)?;
+    }
+    Ok(())
+}
+
+/// Removes a path from the root Cargo.toml `[workspace].exclude` list.
+///
+/// Returns `true` when an entry was removed.
+///
+/// ### Parameters
+/// - `cwd`: The project root containing Cargo.toml
+/// - `plugin_rel`: The path as written in the list, e.g. `generated/plugins/<publisher>/<name>`
+///
+pub fn remove_plugin_from_root_cargo(cwd: &str, plugin_rel: &str) -> Result<bool, String> {
+    let cargo_toml_root_path = Path::new(cwd).join("Cargo.toml");
+    if !cargo_toml_root_path.exists() {
+        return Ok(false);
+    }
+    let mut doc = read_manifest(&cargo_toml_root_path)?;
+    if !remove_from_array(&mut doc, "exclude", plugin_rel) {
+        return Ok(false);
+    }
+    write_manifest(&cargo_toml_root_path, &doc)?;
+    Ok(true)
+}
+
+/// Removes the `[workspace]` section and its `[workspace.*]` subsections from a plugin
+/// Cargo.toml so it can join a parent workspace. Returns `false` when it had none.
+fn strip_workspace_section(doc: &mut DocumentMut) -> bool {
+    doc.remove("workspace").is_some()
+}
+
+/// Converts `generated/plugins` into a Cargo workspace shared by every plugin.
+///
+/// All plugins become members of `generated/plugins/Cargo.toml`, build into the shared
+/// `generated/plugins/target` and reuse compiled dependencies. Their per-plugin
+/// `[workspace]` sections and root exclude entries are replaced by a single exclude of
+/// `generated/plugins`.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+///
+pub fn init_plugins_workspace(cwd: &str) -> Result<(), String> {
+    let root = Path::new(cwd).join(PLUGINS_DIR);
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let mut members: Vec<(String, String)> = Vec::new();
+    for pub_entry in fs::read_dir(&root)
+        .map_err(|e| format!("Failed to list {}: {}", root.display(), e))?
+        .flatten()
+    {
+        let pub_path = pub_entry.path();
+        if !pub_path.is_dir() {
+            continue;
+        }
+        for child in fs::read_dir(&pub_path).into_iter().flatten().flatten() {
+            let p = child.path();
+            if p.join("Cargo.toml").is_file() && p.join("plugin.toml").is_file() {
+                let publisher = pub_entry.file_name().to_string_lossy().to_string();
+                let name = child.file_name().to_string_lossy().to_string();
+                members.push((publisher, name));
+            }
+        }
+    }
+    members.sort();
+
+    let logger = Logger::new();
+    let mut editions: Vec<Option<String>> = Vec::new();
+    let mut added: Vec<String> = Vec::new();
+    for (publisher, name) in &members {
+        let member = format!("{}/{}", publisher, name);
+        let plugin_manifest = root.join(publisher).join(name).join("Cargo.toml");
+        let mut plugin_doc = read_manifest(&plugin_manifest)?;
+        editions.push(
+            plugin_doc
+                .get("package")
+                .and_then(|p| p.get("edition"))
+                .and_then(|e| e.as_str())
+                .map(str::to_string),
+        );
+        if strip_workspace_section(&mut plugin_doc) {
+            write_manifest(&plugin_manifest, &plugin_doc)?;
+        }
+        remove_plugin_from_root_cargo(cwd, &format!("{}/{}", PLUGINS_DIR, member))?;
+        added.push(member);
+    }
+
+    let manifest = plugins_workspace_manifest(cwd);
+    let mut doc = if manifest.is_file() {
+        read_manifest(&manifest)?
+    } else {
+        let template = format!(
+            "# Shared workspace for every plugin in generated/plugins (managed by devapack)\n[workspace]\nresolver = \"{}\"\nmembers = [\n]\n",
+            resolver_for(&editions)
+        );
+        parse_manifest(&template, &manifest)?
+    };
+    for member in &added {
+        add_to_array(&mut doc, "members", member)?;
+        logger.log_message(
+            LogLevel::Info,
+            &format!("Added {} to the workspace", member),
+        );
+    }
+
+    write_manifest(&manifest, &doc)?;
+    add_plugin_to_root_cargo(cwd, PLUGINS_DIR)?;
+
+    logger.log_message(
+        LogLevel::Success,
+        &format!(
+            "Plugins workspace ready at {} ({} plugin(s), shared target dir {}/target)",
+            manifest.display(),
+            members.len(),
+            PLUGINS_DIR
+        ),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty project directory under the system temp dir.
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "devapack-workspace-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn doc(text: &str) -> DocumentMut {
+        text.parse().unwrap()
+    }
+
+    /// The string items of `[workspace].<key>`.
+    fn items(text: &str, key: &str) -> Vec<String> {
+        let mut doc = doc(text);
+        workspace_array(&mut doc, key)
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn adding_to_single_line_array_stays_on_one_line() {
+        let mut d = doc("[workspace]\nmembers = [\".\"]\nexclude = [\"a/b\", \"c/d\"]\n");
+        assert!(add_to_array(&mut d, "exclude", "e/f").unwrap());
+        assert_eq!(
+            d.to_string(),
+            "[workspace]\nmembers = [\".\"]\nexclude = [\"a/b\", \"c/d\", \"e/f\"]\n"
+        );
+    }
+
+    #[test]
+    fn adding_to_multi_line_array_keeps_comments() {
+        let mut d = doc("[workspace]\nmembers = [\n    # plugins\n    \"a/b\" # first\n]\n");
+        assert!(add_to_array(&mut d, "members", "c/d").unwrap());
+        let out = d.to_string();
+        assert_eq!(
+            out,
+            "[workspace]\nmembers = [\n    # plugins\n    \"a/b\", # first\n    \"c/d\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn removing_from_multi_line_array_keeps_comments() {
+        let mut d =
+            doc("[workspace]\nmembers = [\n    \"a/b\", # first\n    # next\n    \"c/d\",\n]\n");
+        assert!(remove_from_array(&mut d, "members", "a/b"));
+        let out = d.to_string();
+        assert_eq!(items(&out, "members"), vec!["c/d"]);
+        assert!(out.contains("# next\n    \"c/d\",\n]"));
+    }
+
+    #[test]
+    fn quoted_commas_and_hashes_are_kept_intact() {
+        let text = "[workspace]\nexclude = [\n    \"odd,path\", # a, b\n    \"has#hash\",\n]\n";
+        let mut d = doc(text);
+        assert!(!add_to_array(&mut d, "exclude", "odd,path").unwrap());
+        assert!(add_to_array(&mut d, "exclude", "x/y").unwrap());
+        assert_eq!(
+            items(&d.to_string(), "exclude"),
+            vec!["odd,path", "has#hash", "x/y"]
+        );
+        assert!(remove_from_array(&mut d, "exclude", "x/y"));
+        assert_eq!(d.to_string(), text);
+        assert!(remove_from_array(&mut d, "exclude", "has#hash"));
+        assert_eq!(items(&d.to_string(), "exclude"), vec!["odd,path"]);
+    }
+
+    #[test]
+    fn adding_twice_is_a_no_op() {
+        let mut d = doc("[workspace]\nexclude = [\"a/b/\"]\n");
+        assert!(!add_to_array(&mut d, "exclude", "a/b").unwrap());
+        assert!(!remove_from_array(&mut d, "exclude", "x/y"));
+        assert_eq!(d.to_string(), "[workspace]\nexclude = [\"a/b/\"]\n");
+    }
+
+    #[test]
+    fn creates_missing_workspace_section() {
+        let mut d = doc("[package]\nname = \"root\"\n");
+        assert!(!remove_from_array(&mut d, "exclude", "a/b"));
+        assert!(add_to_array(&mut d, "exclude", "a/b").unwrap());
+        assert_eq!(
+            d.to_string(),
+            "[package]\nname = \"root\"\n\n[workspace]\nexclude = [\"a/b\"]\n"
+        );
+    }
+
+    #[test]
+    fn creates_missing_key_in_existing_section() {
+        let mut d = doc("[workspace]\nresolver = \"2\"\n");
+        assert!(add_to_array(&mut d, "exclude", "a/b").unwrap());
+        assert_eq!(
+            d.to_string(),
+            "[workspace]\nresolver = \"2\"\nexclude = [\"a/b\"]\n"
+        );
+    }
+
+    #[test]
+    fn leaves_workspace_subsections_alone() {
+        let text = "[workspace.dependencies]\nexclude = [\"dep\"]\n\n[workspace]\nexclude = []\n\n[workspace.metadata]\nmembers = [\"meta\"]\n";
+        assert!(items(text, "exclude").is_empty());
+        assert!(items(text, "members").is_empty());
+
+        let mut d = doc(text);
+        assert!(add_to_array(&mut d, "exclude", "a/b").unwrap());
+        let out = d.to_string();
+        assert!(out.contains("[workspace.dependencies]\nexclude = [\"dep\"]\n"));
+        assert!(out.contains("[workspace]\nexclude = [\"a/b\"]\n"));
+        assert!(out.contains("[workspace.metadata]\nmembers = [\"meta\"]\n"));
+    }
+
+    #[test]
+    fn strips_workspace_and_subsections() {
+        let mut d = doc(
+            "[package]\nname = \"p\"\n\n[workspace]\nmembers = [\n    \".\",\n]\n\n[workspace.dependencies]\nserde = \"1\"\n\n[dependencies]\nserde = \"1\"\n",
+        );
+        assert!(strip_workspace_section(&mut d));
+        assert_eq!(
+            d.to_string(),
+            "[package]\nname = \"p\"\n\n[dependencies]\nserde = \"1\"\n"
+        );
+        assert!(!strip_workspace_section(&mut doc(
+            "[package]\nname = \"p\"\n"
+        )));
+    }
+
+    #[test]
+    fn resolver_follows_member_editions() {
+        let e2024 = Some("2024".to_string());
+        let e2021 = Some("2021".to_string());
+        assert_eq!(resolver_for(&[e2024.clone(), e2024.clone()]), "3");
+        assert_eq!(resolver_for(&[e2024, e2021]), "2");
+        assert_eq!(resolver_for(&[None]), "2");
+        assert_eq!(resolver_for(&[]), "2");
+    }
+
+    #[test]
+    fn register_then_unregister_restores_root_manifest() {
+        let dir = temp_project("root");
+        let cwd = dir.to_str().unwrap();
+        let root = "[package]\nname = \"root\"\n\n[workspace]\nmembers = [\".\"]\nexclude = [\n    # generated\n    \"other/thing\",\n]\n";
+        fs::write(dir.join("Cargo.toml"), root).unwrap();
+
+        register_plugin(cwd, "acme", "synth").unwrap();
+        let registered = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert_eq!(
+            items(&registered, "exclude"),
+            vec!["other/thing", "generated/plugins/acme/synth"]
+        );
+
+        assert!(unregister_plugin(cwd, "acme", "synth").unwrap());
+        assert_eq!(fs::read_to_string(dir.join("Cargo.toml")).unwrap(), root);
+        assert!(!unregister_plugin(cwd, "acme", "synth").unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn register_then_unregister_with_shared_workspace() {
+        let dir = temp_project("shared");
+        let cwd = dir.to_str().unwrap();
+        let plugin_dir = dir.join(PLUGINS_DIR).join("acme").join("synth");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let shared = "[workspace]\nresolver = \"2\"\nmembers = [\n    \"acme/old\",\n]\n";
+        fs::write(plugins_workspace_manifest(cwd), shared).unwrap();
+        fs::write(
+            plugin_dir.join("Cargo.toml"),
+            "[package]\nname = \"synth\"\n\n[workspace]\n",
+        )
+        .unwrap();
+
+        register_plugin(cwd, "acme", "synth").unwrap();
+        let manifest = fs::read_to_string(plugins_workspace_manifest(cwd)).unwrap();
+        assert_eq!(items(&manifest, "members"), vec!["acme/old", "acme/synth"]);
+        assert_eq!(
+            fs::read_to_string(plugin_dir.join("Cargo.toml")).unwrap(),
+            "[package]\nname = \"synth\"\n"
+        );
+
+        assert!(unregister_plugin(cwd, "acme", "synth").unwrap());
+        assert_eq!(
+            fs::read_to_string(plugins_workspace_manifest(cwd)).unwrap(),
+            shared
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn init_derives_resolver_from_plugin_editions() {
+        let dir = temp_project("init");
+        let cwd = dir.to_str().unwrap();
+        let plugin_dir = dir.join(PLUGINS_DIR).join("acme").join("synth");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        fs::write(plugin_dir.join("plugin.toml"), "").unwrap();
+        fs::write(
+            plugin_dir.join("Cargo.toml"),
+            "[package]\nname = \"synth\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+
+        init_plugins_workspace(cwd).unwrap();
+        let manifest = fs::read_to_string(plugins_workspace_manifest(cwd)).unwrap();
+        let parsed = doc(&manifest);
+        assert_eq!(parsed["workspace"]["resolver"].as_str(), Some("2"));
+        assert_eq!(items(&manifest, "members"), vec!["acme/synth"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,33 +1,178 @@
-use crate::utils::signing;
-
-type SignResult = (Option<String>, Option<String>, Option<String>);
-
-/// Sign both raw and gzipped shas (byte slices) and return (sig_raw_b64, sig_gz_b64, pub_b64)
-pub fn sign_two_shas(sha_raw: &[u8], sha_gz: &[u8]) -> Result<SignResult, String> {
-    // Attempt to sign raw sha
-    let mut sig_raw_b64: Option<String> = None;
-    let mut sig_gz_b64: Option<String> = None;
-    let mut pub_b64: Option<String> = None;
-
-    match signing::load_key_bytes() {
-        Ok(_) => {
-            if let Ok((s_raw, p_raw)) = signing::sign_bytes(sha_raw) {
-                sig_raw_b64 = Some(s_raw);
-                pub_b64 = Some(p_raw);
-            }
-            if let Ok((s_gz, p_gz)) = signing::sign_bytes(sha_gz) {
-                sig_gz_b64 = Some(s_gz);
-                // prefer pub from raw; otherwise set from gz
-                if pub_b64.is_none() {
-                    pub_b64 = Some(p_gz);
-                }
-            }
-        }
-        Err(_) => {
-            // no key present, return None for all
-            return Ok((None, None, None));
-        }
+use crate::forge::models::ArchiveUpload;
+use crate::types::addon::AddonSubmissionData;
+use crate::utils::{key_source::KeySource, signing};
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::{Keypair, Signer};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Version of the signature envelope format.
+pub const ENVELOPE_VERSION: u32 = 1;
+/// `type` of the statement an envelope carries, so it cannot be mistaken for another
+/// signed document.
+pub const STATEMENT_TYPE: &str = "devapack.addon-signature";
+
+/// Which bytes of the archive a digest covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveVariant {
+    /// The uncompressed tar
+    Raw,
+    /// The gzipped archive, as uploaded
+    Gzip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedDigest {
+    pub variant: ArchiveVariant,
+    /// Hex SHA-256
+    pub sha256: String,
+}
+
+/// What a submission signature vouches for: this archive, as this version of this addon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedStatement {
+    #[serde(rename = "type")]
+    pub statement_type: String,
+    /// Signature algorithm, `ed25519`
+    pub algorithm: String,
+    /// Fingerprint of `public_key`
+    pub key_id: String,
+    /// Base64 public key
+    pub public_key: String,
+    /// `<publisher>.<name>`
+    pub addon: String,
+    /// Forge id of the addon, once it has one (updates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addon_id: Option<String>,
+    pub addon_version: String,
+    pub digests: Vec<SignedDigest>,
+    /// RFC 3339 time of signing
+    pub signed_at: String,
+}
+
+/// A signed statement, as sent to Forge.
+///
+/// `payload` is the statement's JSON text and `signature` covers exactly those bytes, so
+/// the statement does not have to be re-serialized identically to be checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureEnvelope {
+    pub version: u32,
+    pub payload: String,
+    pub key_id: String,
+    /// Base64 Ed25519 signature of `payload`
+    pub signature: String,
+}
+
+impl SignatureEnvelope {
+    pub fn statement(&self) -> Result<SignedStatement, String> {
+        serde_json::from_str(&self.payload)
+            .map_err(|e| format!("Invalid signature envelope payload: {}", e))
     }
 
-    Ok((sig_raw_b64, sig_gz_b64, pub_b64))
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize envelope: {}", e))
+    }
+}
+
+/// Path of the archive a build writes for an addon: `output/<type>/<publisher>.<name>.tar.gz`.
+///
+/// ### Parameters
+/// - `addon`: The addon being submitted
+///
+pub fn built_archive_path(addon: &AddonSubmissionData) -> Result<PathBuf, String> {
+    Ok(crate::utils::fs::get_cwd()?
+        .join("output")
+        .join(&addon.addon_type)
+        .join(format!("{}.{}.tar.gz", addon.publisher, addon.name)))
+}
+
+/// Reads the built archive of an addon and signs it with the local key, binding its
+/// digests to the addon and version.
+///
+/// The digests are computed here, from the addon's own archive, so the signature cannot
+/// vouch for another file. Without a signing key the archive goes unsigned.
+///
+/// `legacy` adds bare signatures of the two digests for Forge servers that predate the
+/// envelope. Those name no addon or version, so anyone holding them can replay them onto
+/// another upload of the same archive: they are only sent when asked for.
+///
+/// ### Parameters
+/// - `addon`: The addon being submitted
+/// - `legacy`: Also sign the bare digests (`--legacy-signatures`)
+///
+pub fn sign_archive(addon: &AddonSubmissionData, legacy: bool) -> Result<ArchiveUpload, String> {
+    let path = built_archive_path(addon)?;
+    if !path.is_file() {
+        return Err(format!("Built archive not found: {}", path.display()));
+    }
+    let gzip =
+        std::fs::read(&path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let mut raw = Vec::new();
+    GzDecoder::new(&gzip[..])
+        .read_to_end(&mut raw)
+        .map_err(|e| format!("Failed to decompress '{}': {}", path.display(), e))?;
+    let sha_raw = Sha256::digest(&raw);
+    let sha_gz = Sha256::digest(&gzip);
+
+    let mut archive = ArchiveUpload {
+        gzip,
+        sha256: hex::encode(sha_raw),
+        gzip_sha256: hex::encode(sha_gz),
+        envelope: None,
+        signature: None,
+        signature_gzip: None,
+    };
+    if !KeySource::current()?.exists() {
+        return Ok(archive);
+    }
+    let kp = signing::load_keypair()?;
+    archive.envelope = Some(sign_statement(&kp, addon, &sha_raw, &sha_gz)?);
+    if legacy {
+        archive.signature = Some(general_purpose::STANDARD.encode(kp.sign(&sha_raw).to_bytes()));
+        archive.signature_gzip =
+            Some(general_purpose::STANDARD.encode(kp.sign(&sha_gz).to_bytes()));
+    }
+    Ok(archive)
+}
+
+fn sign_statement(
+    kp: &Keypair,
+    addon: &AddonSubmissionData,
+    sha_raw: &[u8],
+    sha_gz: &[u8],
+) -> Result<SignatureEnvelope, String> {
+    let key_id = signing::fingerprint(&kp.public);
+    let statement = SignedStatement {
+        statement_type: STATEMENT_TYPE.to_string(),
+        algorithm: "ed25519".to_string(),
+        key_id: key_id.clone(),
+        public_key: general_purpose::STANDARD.encode(kp.public.to_bytes()),
+        addon: format!("{}.{}", addon.publisher, addon.name),
+        addon_id: addon.id.clone(),
+        addon_version: addon.version.clone(),
+        digests: vec![
+            SignedDigest {
+                variant: ArchiveVariant::Raw,
+                sha256: hex::encode(sha_raw),
+            },
+            SignedDigest {
+                variant: ArchiveVariant::Gzip,
+                sha256: hex::encode(sha_gz),
+            },
+        ],
+        signed_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let payload = serde_json::to_string(&statement)
+        .map_err(|e| format!("Failed to serialize signed statement: {}", e))?;
+    let signature = kp.sign(payload.as_bytes());
+    Ok(SignatureEnvelope {
+        version: ENVELOPE_VERSION,
+        payload,
+        key_id,
+        signature: general_purpose::STANDARD.encode(signature.to_bytes()),
+    })
 }
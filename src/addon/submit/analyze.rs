@@ -1,4 +1,7 @@
-use crate::types::addon::{AddonInfo, AddonMetadata};
+use crate::{
+    types::addon::{AddonInfo, AddonMetadata},
+    utils::engine,
+};
 
 pub async fn analyze_addon(selected_addon: &AddonInfo) -> Result<AddonMetadata, String> {
     let addon_toml_file = match selected_addon.addon_type.as_str() {
@@ -49,10 +52,13 @@ pub async fn analyze_addon(selected_addon: &AddonInfo) -> Result<AddonMetadata,
         .unwrap_or("unknown")
         .to_string();
 
+    let engine = engine::manifest_requirement(&toml_content, selected_addon.addon_type.as_str())?;
+
     Ok(AddonMetadata {
         name,
         version,
         access,
         publisher,
+        engine,
     })
 }
@@ -1,17 +1,15 @@
-use crate::builder::{bank as bank_builder, plugin as plugin_builder};
+use crate::builder::{bank as bank_builder, plugin as plugin_builder, sign::SignatureOptions};
 use crate::{
-    addon::{
-        publish::request::post_publish_addon_to_forge_api,
-        submit::{
-            analyze::analyze_addon, discover::discover_addons, request::post_addon_to_forge_api,
-        },
+    addon::submit::{
+        analyze::analyze_addon, discover::discover_addons, request::post_addon_to_forge_api,
     },
+    forge::{ForgeClient, models::SignRequest},
     types::addon::AddonSubmissionData,
     utils::logger::{LogLevel, Logger},
     utils::spinner::with_spinner,
 };
 
-pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
+pub async fn prompt_submit_addon(cwd: &str, legacy_signatures: bool) -> Result<(), String> {
     println!();
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!("Devalang Addon Submitter");
@@ -80,9 +78,23 @@ pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
         }
     };
 
+    // Ensure keypair exists (create if missing) and unlock it before the spinners start,
+    // since both may prompt
+    if let Err(e) = crate::utils::signing::ensure_keypair() {
+        // In CI an unsigned submission is not an option
+        if crate::utils::key_source::ci_mode() {
+            return Err(e);
+        }
+        Logger::new().log_message(
+            LogLevel::Warning,
+            &format!("Failed to ensure signing keypair: {}", e),
+        );
+    }
+    crate::utils::signing::unlock()?;
+
     let submit_addon_spinner = with_spinner("Submitting addon...");
 
-    let submission_data = AddonSubmissionData {
+    let mut submission_data = AddonSubmissionData {
         id: None,
         name: addon_metadata.name.clone(),
         addon_type: selected_addon.addon_type.clone(),
@@ -91,17 +103,28 @@ pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
         access: addon_metadata.access.clone(),
         files: selected_addon.files.clone(),
         publisher: addon_metadata.publisher.clone(),
+        engine: addon_metadata.engine.clone(),
     };
 
     // Build the addon before submitting (produces .tar.gz in output/)
     {
         let build_spinner = with_spinner("Building addon before submit...");
         let build_result = match submission_data.addon_type.as_str() {
-            "bank" => bank_builder::build_bank(&submission_data.path, cwd),
+            "bank" => {
+                bank_builder::build_bank(&submission_data.path, cwd, SignatureOptions::default())
+            }
             "plugin" =>
             // Align with update flow: do not show summary during submit build
             {
-                plugin_builder::build_plugin(&submission_data.path, &false, cwd, false, false)
+                plugin_builder::build_plugin(
+                    &submission_data.path,
+                    &false,
+                    cwd,
+                    SignatureOptions::default(),
+                    false,
+                    plugin_builder::PluginTarget::Wasm,
+                    false,
+                )
             }
             _ => Err("Unknown addon type for build".to_string()),
         };
@@ -111,44 +134,35 @@ pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
         }
     }
 
-    // Ensure keypair exists (create if missing)
-    if let Err(e) = crate::utils::signing::ensure_keypair() {
-        Logger::new().log_message(
-            LogLevel::Warning,
-            &format!("Failed to ensure signing keypair: {}", e),
-        );
-    }
+    // A plugin build records the devalang requirement it was built for
+    submission_data.engine = analyze_addon(selected_addon).await?.engine;
 
-    let (addon_id_opt, sig_opt, pub_opt, sha_opt) =
-        match post_addon_to_forge_api(&submission_data).await {
+    let (addon_id, envelope_opt, sha_opt) =
+        match post_addon_to_forge_api(&submission_data, legacy_signatures).await {
             Ok(tuple) => tuple,
             Err(e) => {
                 return Err(format!("Failed to submit addon: {}", e));
             }
         };
 
-    let addon_id = match addon_id_opt {
-        Some(id) => id,
-        None => {
-            return Err("Addon ID missing after submission".to_string());
-        }
-    };
-
-    // If signature & pubkey were produced by the client and returned, call the sign endpoint to register them.
-    if let (Some(sig_b64), Some(pub_b64), Some(sha_hex)) = (sig_opt, pub_opt, sha_opt) {
-        match crate::addon::remote_sign::register_signature_with_server(
-            &addon_id, &sig_b64, &pub_b64, &sha_hex,
-        )
-        .await
+    // If the client signed the archive, call the sign endpoint to register the envelope.
+    if let (Some(envelope), Some(sha_hex)) = (envelope_opt, sha_opt) {
+        let request = SignRequest {
+            public_key: envelope.statement()?.public_key,
+            signature_envelope: &envelope,
+            archive_sha256: &sha_hex,
+        };
+        match ForgeClient::authenticated()?
+            .sign_addon(&addon_id, &request)
+            .await
         {
-            Ok(json) => {
-                let payload = json.get("payload").unwrap_or(&json);
-                if let Ok(key_path) = crate::utils::signing::key_path() {
-                    crate::addon::summary::print_addon_summary(payload, &key_path);
+            Ok(payload) => {
+                if let Ok(source) = crate::utils::key_source::KeySource::current() {
+                    crate::addon::summary::print_addon_summary(&payload, &source.describe());
                 }
             }
             Err(e) => {
-                return Err(e);
+                return Err(format!("Failed to register signature: {}", e));
             }
         }
     }
@@ -168,7 +182,7 @@ pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
     if publish_confirmation {
         let publish_addon_spinner = with_spinner("Publishing addon...");
 
-        if let Err(e) = post_publish_addon_to_forge_api(&Some(addon_id.clone())).await {
+        if let Err(e) = ForgeClient::authenticated()?.publish_addon(&addon_id).await {
             return Err(format!("Failed to publish addon: {}", e));
         }
 
@@ -1,8 +1,7 @@
 use crate::utils::logger::{LogLevel, Logger};
 use serde_json::Value;
-use std::path::Path;
 
-pub fn print_addon_summary(response_json: &Value, key_path: &Path) {
+pub fn print_addon_summary(response_json: &Value, key_source: &str) {
     let logger = Logger::new();
     // Helpers to try multiple nested paths and return a string representation
     fn get_path<'a>(v: &'a Value, path: &[&str]) -> Option<&'a Value> {
@@ -26,10 +25,7 @@ pub fn print_addon_summary(response_json: &Value, key_path: &Path) {
         None
     }
 
-    logger.log_message(
-        LogLevel::Info,
-        &format!("🔑 Using key : {}", key_path.display()),
-    );
+    logger.log_message(LogLevel::Info, &format!("🔑 Using key : {}", key_source));
 
     // Friendly message if present
     if let Some(msg) = get_any_str(response_json, &[&["message"], &["msg"]]) {
@@ -0,0 +1,227 @@
+use crate::utils::fs as ufs;
+use std::path::{Path, PathBuf};
+
+/// Addon kind a template scaffolds; also the template sub-directory name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    Plugin,
+    Bank,
+}
+
+impl TemplateKind {
+    fn dir_name(self) -> &'static str {
+        match self {
+            TemplateKind::Plugin => "plugin",
+            TemplateKind::Bank => "bank",
+        }
+    }
+}
+
+/// A template found in one of the template directories.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Directories searched for templates, in priority order: the project's
+/// `.devalang/templates/<kind>/`, then `~/.devalang/templates/<kind>/`.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `kind`: The addon kind
+///
+pub fn template_dirs(cwd: &str, kind: TemplateKind) -> Vec<PathBuf> {
+    let mut dirs = vec![
+        Path::new(cwd)
+            .join(".devalang")
+            .join("templates")
+            .join(kind.dir_name()),
+    ];
+    if let Ok(home) = ufs::get_user_home() {
+        dirs.push(
+            home.join(".devalang")
+                .join("templates")
+                .join(kind.dir_name()),
+        );
+    }
+    dirs
+}
+
+/// Lists the templates available for `kind`. A project template hides a user template
+/// with the same name.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `kind`: The addon kind
+///
+pub fn discover_templates(cwd: &str, kind: TemplateKind) -> Vec<Template> {
+    let mut templates: Vec<Template> = Vec::new();
+    for dir in template_dirs(cwd, kind) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<Template> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .filter_map(|p| {
+                let name = p.file_name()?.to_str()?.to_string();
+                Some(Template { name, path: p })
+            })
+            .filter(|t| !templates.iter().any(|existing| existing.name == t.name))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        templates.extend(found);
+    }
+    templates
+}
+
+/// Resolves `--template <dir>`: an existing directory (absolute or relative to `cwd`),
+/// otherwise the name of a discovered template.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `kind`: The addon kind
+/// - `spec`: The value given on the command line
+///
+pub fn resolve_template(cwd: &str, kind: TemplateKind, spec: &str) -> Result<PathBuf, String> {
+    let given = Path::new(spec);
+    let candidate = if given.is_absolute() {
+        given.to_path_buf()
+    } else {
+        Path::new(cwd).join(given)
+    };
+    if candidate.is_dir() {
+        return Ok(candidate);
+    }
+
+    if let Some(t) = discover_templates(cwd, kind)
+        .into_iter()
+        .find(|t| t.name == spec)
+    {
+        return Ok(t.path);
+    }
+
+    let searched: Vec<String> = template_dirs(cwd, kind)
+        .iter()
+        .map(|d| d.display().to_string())
+        .collect();
+    Err(format!(
+        "Template '{}' not found: not a directory and not in {}",
+        spec,
+        searched.join(" or ")
+    ))
+}
+
+/// Copies a template directory into `dest`, substituting `{{key}}` placeholders in file
+/// names and in the contents of text files. Binary files are copied unchanged.
+///
+/// ### Parameters
+/// - `template_dir`: The template to copy
+/// - `dest`: The addon directory to fill (created if missing)
+/// - `vars`: Placeholder names and their values, e.g. `("name", "synth")`
+///
+pub fn copy_template(
+    template_dir: &Path,
+    dest: &Path,
+    vars: &[(&str, &str)],
+) -> Result<(), String> {
+    let files = ufs::walk_files(template_dir)?;
+    for file in files {
+        let Some(rel) = ufs::path_relative_to(&file, template_dir) else {
+            continue;
+        };
+        if rel.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(ufs::is_ignored_component)
+        }) {
+            continue;
+        }
+
+        let rel_out = substitute(&ufs::to_unix_string(&rel), vars);
+        let target = dest.join(&rel_out);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        let bytes = std::fs::read(&file)
+            .map_err(|e| format!("Failed to read template file {}: {}", file.display(), e))?;
+        let out = match String::from_utf8(bytes) {
+            Ok(text) => substitute(&text, vars).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        std::fs::write(&target, out)
+            .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Replaces `{{key}}` (spaces inside the braces allowed) with its value. Unknown
+/// placeholders are left untouched.
+///
+/// ### Parameters
+/// - `text`: The text to expand
+/// - `vars`: Placeholder names and their values
+///
+pub fn substitute(text: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        let key = after[..close].trim();
+        out.push_str(&rest[..open]);
+        match vars.iter().find(|(k, _)| *k == key) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[open..open + 2 + close + 2]),
+        }
+        rest = &after[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARS: &[(&str, &str)] = &[("name", "synth"), ("publisher", "acme")];
+
+    #[test]
+    fn text_without_placeholders_is_unchanged() {
+        assert_eq!(substitute("plain text", VARS), "plain text");
+        assert_eq!(substitute("", VARS), "");
+        assert_eq!(substitute("a { b } c", VARS), "a { b } c");
+    }
+
+    #[test]
+    fn replaces_known_placeholders() {
+        assert_eq!(substitute("name = \"{{name}}\"", VARS), "name = \"synth\"");
+        assert_eq!(substitute("{{ publisher }}/x", VARS), "acme/x");
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders_untouched() {
+        assert_eq!(
+            substitute("{{ version }} of {{name}}", VARS),
+            "{{ version }} of synth"
+        );
+    }
+
+    #[test]
+    fn replaces_repeated_and_adjacent_placeholders() {
+        assert_eq!(substitute("{{name}}-{{name}}", VARS), "synth-synth");
+        assert_eq!(substitute("{{publisher}}{{name}}", VARS), "acmesynth");
+        assert_eq!(substitute("{{other}}{{name}}", VARS), "{{other}}synth");
+    }
+
+    #[test]
+    fn keeps_an_unclosed_placeholder() {
+        assert_eq!(substitute("{{name}} {{name", VARS), "synth {{name");
+    }
+}
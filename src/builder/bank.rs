@@ -1,7 +1,17 @@
-use crate::utils::fs as ufs;
+use crate::builder::{
+    cache::{self, BuildStatus},
+    readme::{self, TriggerDoc},
+    sign::{self, SignatureOptions},
+};
+use crate::utils::{
+    audio,
+    engine::{self, EngineSection},
+    fs as ufs, logger, spinner,
+};
 use flate2::Compression;
 use flate2::write::GzEncoder;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tar::Builder as TarBuilder;
@@ -16,12 +26,17 @@ struct BankSection {
     version: Option<String>,
     #[serde(default)]
     access: Option<String>,
+    #[serde(default)]
+    engine: Option<EngineSection>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct TriggerEntry {
     name: String,
     path: String,
+    /// Hex SHA-256 of the audio file, checked by `devapack verify`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -29,6 +44,16 @@ struct BankToml {
     bank: BankSection,
     #[serde(default)]
     triggers: Vec<TriggerEntry>,
+    #[serde(default)]
+    build: Option<BuildSection>,
+}
+
+/// `[build]` options of bank.toml.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BuildSection {
+    /// Sign the archive on every build, as `--sign` does
+    #[serde(default)]
+    sign: bool,
 }
 
 /// Builds a bank located at the given path.
@@ -36,8 +61,9 @@ struct BankToml {
 /// ### Parameters
 /// - `path`: The path of the bank
 /// - `cwd`: The current working directory
+/// - `signature`: Whether to sign the archive and whether a valid signature is required
 ///
-pub fn build_bank(path: &str, cwd: &str) -> Result<(), String> {
+pub fn build_bank(path: &str, cwd: &str, signature: SignatureOptions) -> Result<(), String> {
     let bank_dir = resolve_bank_dir(cwd, path)?;
 
     let bank_toml_path = bank_dir.join("bank.toml");
@@ -54,6 +80,15 @@ pub fn build_bank(path: &str, cwd: &str) -> Result<(), String> {
         toml::from_str(&txt).map_err(|e| format!("Invalid TOML: {}", e))?
     };
 
+    if let Some(req) = bank_doc
+        .bank
+        .engine
+        .as_ref()
+        .and_then(|e| e.devalang.as_ref())
+    {
+        engine::parse_requirement(req)?;
+    }
+
     let audio_dir = bank_dir.join("audio");
     if !audio_dir.is_dir() {
         return Err(format!(
@@ -73,6 +108,8 @@ pub fn build_bank(path: &str, cwd: &str) -> Result<(), String> {
         return Err("Fields [bank].publisher and [bank].name are required in bank.toml".into());
     }
 
+    refresh_bank_readme(&bank_dir, &audio_dir, &bank_doc)?;
+
     let out_root = Path::new(cwd).join("output").join("bank");
     fs::create_dir_all(&out_root)
         .map_err(|e| format!("Failed to create output directory: {}", e))?;
@@ -84,20 +121,40 @@ pub fn build_bank(path: &str, cwd: &str) -> Result<(), String> {
         &audio_dir,
         &out_file,
         &publisher,
-        &name,
-        bank_doc.bank.description.clone(),
     )?;
-    println!("✅ Bank built: {}", out_file.to_string_lossy());
+
+    let mut outputs = vec![out_file.clone()];
+    let manifest_sign = bank_doc.build.as_ref().is_some_and(|build| build.sign);
+    outputs.extend(sign::sign_and_check(&out_file, signature, manifest_sign)?);
+    logger::print_line(&format!("✅ Bank built: {}", out_file.to_string_lossy()));
+
+    // Fingerprint after the build: it rewrites bank.toml and README.md
+    let options = signature.cache_key();
+    if let Err(e) = ufs::addon_files(&bank_dir)
+        .and_then(|inputs| cache::record(cwd, "bank", &bank_dir, &inputs, &options, &outputs))
+    {
+        logger::print_line(&format!("⚠️  Failed to update the build cache: {}", e));
+    }
 
     Ok(())
 }
 
 /// Builds all banks in the generated directory.
 ///
+/// Banks whose files are unchanged since their last build are skipped.
+///
 /// ### Parameters
 /// - `cwd`: The current working directory
+/// - `signature`: Whether to sign each archive and whether a valid signature is required
+/// - `force`: Rebuild every bank, ignoring the build cache
+/// - `jobs`: The maximum number of banks built at once
 ///
-pub fn build_all_banks(cwd: &str) -> Result<(), String> {
+pub fn build_all_banks(
+    cwd: &str,
+    signature: SignatureOptions,
+    force: bool,
+    jobs: usize,
+) -> Result<(), String> {
     let banks_root = Path::new(cwd).join("generated").join("banks");
     if !banks_root.exists() {
         return Err(format!(
@@ -134,17 +191,53 @@ pub fn build_all_banks(cwd: &str) -> Result<(), String> {
     bank_dirs.sort();
 
     let mut errors: Vec<String> = Vec::new();
+    let mut cached = 0;
     let total = bank_dirs.len();
-    for p in bank_dirs {
-        let p_str = p.to_string_lossy().to_string();
-        match build_bank(&p_str, cwd) {
-            Ok(_) => {}
+    let options = signature.cache_key();
+    // Unlock the key up front: a passphrase prompt cannot share the terminal with spinners
+    if signature.sign
+        || bank_dirs
+            .iter()
+            .any(|d| sign::manifest_requests_signing(&d.join("bank.toml")))
+    {
+        sign::prepare_key()?;
+    }
+    let results = spinner::run_parallel(
+        bank_dirs,
+        jobs,
+        |p| {
+            ufs::path_relative_to(p, &banks_root)
+                .map(ufs::to_unix_string)
+                .unwrap_or_else(|| p.to_string_lossy().to_string())
+        },
+        |p| {
+            let p_str = p.to_string_lossy().to_string();
+            if !force
+                && ufs::addon_files(&p)
+                    .map(|inputs| cache::is_fresh(cwd, "bank", &p, &inputs, &options))
+                    .unwrap_or(false)
+            {
+                logger::print_line(&format!("⏭️  Cached (unchanged): {}", p_str));
+                return (p_str, Ok(BuildStatus::Cached));
+            }
+            let result = build_bank(&p_str, cwd, signature).map(|_| BuildStatus::Built);
+            (p_str, result)
+        },
+    );
+    for (p_str, result) in results {
+        match result {
+            Ok(BuildStatus::Built) => {}
+            Ok(BuildStatus::Cached) => cached += 1,
             Err(e) => errors.push(format!("{} -> {}", p_str, e)),
         }
     }
 
     if errors.is_empty() {
-        println!("✅ Build complete: {} bank(s) built", total);
+        println!(
+            "✅ Build complete: {} bank(s) built, {} cached",
+            total - cached,
+            cached
+        );
         Ok(())
     } else {
         let joined = errors.join("\n - ");
@@ -252,15 +345,56 @@ fn discover_triggers(audio_dir: &Path) -> Result<Vec<TriggerEntry>, String> {
             .and_then(|s| s.to_str())
             .unwrap_or("")
             .to_string();
+        let bytes = fs::read(&p).map_err(|e| format!("Failed to read {}: {}", p.display(), e))?;
         out.push(TriggerEntry {
             name,
             path: rel_str,
+            sha256: Some(hex::encode(Sha256::digest(&bytes))),
         });
     }
     out.sort_by(|a, b| a.path.cmp(&b.path));
     Ok(out)
 }
 
+/// Regenerates the marked section of the bank README from bank.toml and the audio files.
+///
+/// ### Parameters
+/// - `bank_dir`: The bank directory
+/// - `audio_dir`: The bank's audio directory
+/// - `bank_doc`: The parsed bank.toml, with discovered triggers merged in
+///
+fn refresh_bank_readme(
+    bank_dir: &Path,
+    audio_dir: &Path,
+    bank_doc: &BankToml,
+) -> Result<(), String> {
+    let bank = &bank_doc.bank;
+    let triggers: Vec<TriggerDoc> = bank_doc
+        .triggers
+        .iter()
+        .map(|t| {
+            let rel = t.path.trim_start_matches("./");
+            TriggerDoc {
+                name: t.name.clone(),
+                path: format!("audio/{}", rel),
+                audio: audio::probe(&audio_dir.join(rel)),
+            }
+        })
+        .collect();
+    let section = readme::bank_section(
+        &bank.publisher,
+        &bank.name,
+        bank.version.as_deref(),
+        bank.engine.as_ref().and_then(|e| e.devalang.as_deref()),
+        &triggers,
+    );
+    readme::refresh_readme(
+        &bank_dir.join("README.md"),
+        &section,
+        &default_readme_bank(&bank.publisher, &bank.name, bank.description.as_deref()),
+    )
+}
+
 /// Creates a ZIP archive of the bank directory.
 ///
 /// ### Parameters
@@ -269,8 +403,6 @@ fn discover_triggers(audio_dir: &Path) -> Result<Vec<TriggerEntry>, String> {
 /// - `audio_dir`: The path to the audio directory.
 /// - `out_file`: The output ZIP file path.
 /// - `publisher`: The publisher of the bank.
-/// - `name`: The name of the bank.
-/// - `description`: An optional description of the bank.
 ///
 fn create_bank_tar_gz(
     bank_dir: &Path,
@@ -278,8 +410,6 @@ fn create_bank_tar_gz(
     audio_dir: &Path,
     out_file: &Path,
     publisher: &str,
-    name: &str,
-    description: Option<String>,
 ) -> Result<(), String> {
     let file =
         fs::File::create(out_file).map_err(|e| format!("Failed to create output file: {}", e))?;
@@ -290,23 +420,9 @@ fn create_bank_tar_gz(
     tar.append_path_with_name(bank_toml_path, "bank.toml")
         .map_err(|e| format!("Failed to add bank.toml to tar: {}", e))?;
 
-    // README.md (from bank dir if present, else default)
-    let readme_path = bank_dir.join("README.md");
-    if readme_path.exists() {
-        tar.append_path_with_name(&readme_path, "README.md")
-            .map_err(|e| format!("Failed to add README.md to tar: {}", e))?;
-    } else {
-        let readme = default_readme_bank(publisher, name, description.as_deref());
-        let mut header = tar::Header::new_gnu();
-        header
-            .set_path("README.md")
-            .map_err(|e| format!("Failed to set header path: {}", e))?;
-        header.set_size(readme.len() as u64);
-        header.set_mode(0o644);
-        header.set_cksum();
-        tar.append(&header, readme.as_bytes())
-            .map_err(|e| format!("Failed to append README.md to tar: {}", e))?;
-    }
+    // README.md (refreshed, or created, just before packaging)
+    tar.append_path_with_name(bank_dir.join("README.md"), "README.md")
+        .map_err(|e| format!("Failed to add README.md to tar: {}", e))?;
 
     // LICENSE (from bank dir if present, else default MIT)
     let license_path = bank_dir.join("LICENSE");
@@ -429,6 +545,9 @@ fn write_triggers_after_bank(
             trig_lines.push("[[triggers]]".to_string());
             trig_lines.push(format!("name = \"{}\"", t.name));
             trig_lines.push(format!("path = \"{}\"", t.path));
+            if let Some(sha) = &t.sha256 {
+                trig_lines.push(format!("sha256 = \"{}\"", sha));
+            }
             if i + 1 < triggers.len() {
                 trig_lines.push(String::new());
             }
@@ -475,11 +594,16 @@ fn merge_triggers(existing: Vec<TriggerEntry>, discovered: Vec<TriggerEntry>) ->
             final_triggers.push(TriggerEntry {
                 name: existing_name.clone(),
                 path,
+                sha256: d.sha256,
             });
         } else {
             let base = d.name;
             let unique = disambiguate_name(&base, &path, &mut used_names);
-            final_triggers.push(TriggerEntry { name: unique, path });
+            final_triggers.push(TriggerEntry {
+                name: unique,
+                path,
+                sha256: d.sha256,
+            });
         }
     }
     final_triggers.sort_by(|a, b| a.path.cmp(&b.path));
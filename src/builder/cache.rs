@@ -0,0 +1,132 @@
+use crate::utils::{fs as ufs, version::get_version};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of one addon in a build of all addons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStatus {
+    Built,
+    /// Skipped: unchanged since its last build
+    Cached,
+}
+
+/// What `output/.cache/<kind>/<addon>.json` remembers about the last successful build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    fingerprint: String,
+    devapack_version: String,
+    /// Artifacts of that build, relative to the project root
+    outputs: Vec<String>,
+    built_at: String,
+}
+
+/// Whether an addon is unchanged since its last successful build with the same options,
+/// and that build's artifacts are still in place.
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `kind`: `bank` or `plugin`
+/// - `addon_dir`: The addon directory
+/// - `inputs`: Every file the build reads, as `(relative path, path)` pairs
+/// - `options`: Build options that change the artifacts, e.g. the plugin target
+///
+pub fn is_fresh(
+    cwd: &str,
+    kind: &str,
+    addon_dir: &Path,
+    inputs: &[(String, PathBuf)],
+    options: &str,
+) -> bool {
+    let Ok(txt) = fs::read_to_string(entry_path(cwd, kind, addon_dir)) else {
+        return false;
+    };
+    let Ok(entry) = serde_json::from_str::<CacheEntry>(&txt) else {
+        return false;
+    };
+    let Ok(fingerprint) = fingerprint(inputs, options) else {
+        return false;
+    };
+    entry.fingerprint == fingerprint
+        && entry
+            .outputs
+            .iter()
+            .all(|o| Path::new(cwd).join(o).is_file())
+}
+
+/// Records a successful build so the next `build` of all addons can skip it.
+///
+/// Called after the build, since building rewrites some inputs (manifest, README).
+///
+/// ### Parameters
+/// - `cwd`: The project root
+/// - `kind`: `bank` or `plugin`
+/// - `addon_dir`: The addon directory
+/// - `inputs`: Every file the build reads, as `(relative path, path)` pairs
+/// - `options`: The build options passed to `is_fresh`
+/// - `outputs`: The artifacts the build produced
+///
+pub fn record(
+    cwd: &str,
+    kind: &str,
+    addon_dir: &Path,
+    inputs: &[(String, PathBuf)],
+    options: &str,
+    outputs: &[PathBuf],
+) -> Result<(), String> {
+    let entry = CacheEntry {
+        fingerprint: fingerprint(inputs, options)?,
+        devapack_version: get_version(),
+        outputs: outputs
+            .iter()
+            .map(|o| {
+                ufs::path_relative_to(o, Path::new(cwd))
+                    .map(ufs::to_unix_string)
+                    .unwrap_or_else(|| o.to_string_lossy().into_owned())
+            })
+            .collect(),
+        built_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let path = entry_path(cwd, kind, addon_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize build cache entry: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Hash of the devapack version, the build options and the path and content of every input.
+fn fingerprint(inputs: &[(String, PathBuf)], options: &str) -> Result<String, String> {
+    let mut sorted: Vec<&(String, PathBuf)> = inputs.iter().collect();
+    sorted.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("devapack {}\n{}\n", get_version(), options));
+    for (rel, path) in sorted {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update(Sha256::digest(&bytes));
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// `output/.cache/<kind>/<publisher>.<name>.json`, named after the addon's place under
+/// `generated/<kind>s/`.
+pub fn entry_path(cwd: &str, kind: &str, addon_dir: &Path) -> PathBuf {
+    let kind_root = Path::new(cwd).join("generated").join(format!("{}s", kind));
+    let id = match ufs::path_relative_to(addon_dir, &kind_root) {
+        Some(rel) if !rel.as_os_str().is_empty() => ufs::to_unix_string(&rel).replace('/', "."),
+        // Addons outside generated/ are keyed by their path
+        _ => hex::encode(Sha256::digest(addon_dir.to_string_lossy().as_bytes()))[..16].to_string(),
+    };
+    Path::new(cwd)
+        .join("output")
+        .join(".cache")
+        .join(kind)
+        .join(format!("{}.json", id))
+}
@@ -0,0 +1,303 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    spinner,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Jobserver handed to every cargo build while `with_shared_jobserver` runs.
+static JOBSERVER: Mutex<Option<jobserver::Client>> = Mutex::new(None);
+
+/// One line of `cargo build --message-format=json` output.
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    manifest_path: Option<String>,
+    #[serde(default)]
+    target: Option<CargoTarget>,
+    #[serde(default)]
+    filenames: Vec<String>,
+    #[serde(default)]
+    message: Option<CompilerMessage>,
+    #[serde(default)]
+    success: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoTarget {
+    #[serde(default)]
+    kind: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompilerMessage {
+    level: String,
+    message: String,
+    #[serde(default)]
+    rendered: Option<String>,
+    #[serde(default)]
+    spans: Vec<serde_json::Value>,
+}
+
+/// Result of a cargo build: the cdylib files it produced and the diagnostics it emitted.
+#[derive(Debug, Default)]
+pub struct CargoBuild {
+    pub artifacts: Vec<PathBuf>,
+    pub diagnostics: Vec<(LogLevel, String)>,
+    pub success: bool,
+    pub stderr: String,
+}
+
+impl CargoBuild {
+    /// Returns the produced artifact whose file name ends with `suffix` (e.g. `.wasm`).
+    pub fn artifact_with_suffix(&self, suffix: &str) -> Option<&Path> {
+        self.artifacts
+            .iter()
+            .find(|p| {
+                p.file_name()
+                    .and_then(|f| f.to_str())
+                    .map(|f| f.ends_with(suffix))
+                    .unwrap_or(false)
+            })
+            .map(|p| p.as_path())
+    }
+}
+
+/// Runs `f` with one jobserver shared by all the cargo builds it starts, so builds running
+/// side by side share the CPUs instead of each starting one compile job per core.
+///
+/// ### Parameters
+/// - `builds`: The number of cargo builds that may run at once
+/// - `f`: The work starting the builds
+///
+pub fn with_shared_jobserver<T>(builds: usize, f: impl FnOnce() -> T) -> T {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    // Each cargo process also holds one implicit token of its own
+    let tokens = cpus.saturating_sub(builds).max(1);
+    let client = match jobserver::Client::new(tokens) {
+        Ok(c) => Some(c),
+        Err(e) => {
+            Logger::new().log_message(
+                LogLevel::Warning,
+                &format!(
+                    "Failed to create a jobserver, cargo builds will not share CPUs: {}",
+                    e
+                ),
+            );
+            None
+        }
+    };
+    *JOBSERVER.lock().unwrap() = client;
+    let value = f();
+    *JOBSERVER.lock().unwrap() = None;
+    value
+}
+
+/// Builds the cdylib of the crate in `crate_dir`, reading artifact paths from cargo's JSON messages.
+///
+/// Only the crate's own cdylib is kept: a successful build that produced none is an error,
+/// even when a dependency built one.
+///
+/// ### Parameters
+/// - `crate_dir`: The directory containing the crate's Cargo.toml
+/// - `release`: Whether to build with `--release`
+/// - `triple`: Cross-compilation target, or `None` for the host
+///
+pub fn build_cdylib(
+    crate_dir: &Path,
+    release: bool,
+    triple: Option<&str>,
+) -> Result<CargoBuild, String> {
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(crate_dir);
+    cmd.arg("build");
+    cmd.arg("--message-format=json");
+    if let Some(t) = triple {
+        cmd.arg("--target");
+        cmd.arg(t);
+    }
+    if release {
+        cmd.arg("--release");
+    }
+    if let Some(client) = JOBSERVER.lock().unwrap().as_ref() {
+        client.configure(&mut cmd);
+    }
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run cargo build: {}", e))?;
+
+    let manifest = crate_dir.join("Cargo.toml");
+    let own_manifest = manifest
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", manifest.display(), e))?;
+    let mut own_artifacts: Vec<PathBuf> = Vec::new();
+    let mut other_cdylibs: Vec<PathBuf> = Vec::new();
+    let mut build = CargoBuild {
+        success: output.status.success(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        ..Default::default()
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(msg) = serde_json::from_str::<CargoMessage>(line) else {
+            continue;
+        };
+        match msg.reason.as_str() {
+            "compiler-artifact" => {
+                let is_cdylib = msg
+                    .target
+                    .as_ref()
+                    .map(|t| t.kind.iter().any(|k| k == "cdylib"))
+                    .unwrap_or(false);
+                if !is_cdylib {
+                    continue;
+                }
+                let is_own = msg
+                    .manifest_path
+                    .as_deref()
+                    .and_then(|m| Path::new(m).canonicalize().ok())
+                    .is_some_and(|m| m == own_manifest);
+                let files = msg.filenames.into_iter().map(PathBuf::from);
+                if is_own {
+                    own_artifacts.extend(files);
+                } else {
+                    other_cdylibs.extend(files);
+                }
+            }
+            "compiler-message" => {
+                if let Some(diag) = msg.message.and_then(into_diagnostic) {
+                    build.diagnostics.push(diag);
+                }
+            }
+            "build-finished" => {
+                if let Some(ok) = msg.success {
+                    build.success = build.success && ok;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // A dependency's cdylib must never be packaged as the plugin
+    if build.success && own_artifacts.is_empty() {
+        let others: Vec<String> = other_cdylibs
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(format!(
+            "cargo built no cdylib for {}{}; check that it has `crate-type = [\"cdylib\"]` under [lib]",
+            own_manifest.display(),
+            if others.is_empty() {
+                String::new()
+            } else {
+                format!(" (only for its dependencies: {})", others.join(", "))
+            }
+        ));
+    }
+    build.artifacts = own_artifacts;
+    Ok(build)
+}
+
+/// Runs [`build_cdylib`] behind a spinner, then reports diagnostics through the `Logger`.
+///
+/// ### Parameters
+/// - `label`: The spinner message
+/// - `crate_dir`: The directory containing the crate's Cargo.toml
+/// - `release`: Whether to build with `--release`
+/// - `triple`: Cross-compilation target, or `None` for the host
+///
+pub fn build_cdylib_step(
+    label: &str,
+    crate_dir: &Path,
+    release: bool,
+    triple: Option<&str>,
+) -> Result<CargoBuild, String> {
+    let spinner = spinner::with_spinner(label);
+    let build = match build_cdylib(crate_dir, release, triple) {
+        Ok(b) => b,
+        Err(e) => {
+            spinner.fail(e.clone());
+            return Err(e);
+        }
+    };
+    spinner.finish_and_clear();
+
+    log_diagnostics(&build.diagnostics);
+
+    if !build.success {
+        let errors = build
+            .diagnostics
+            .iter()
+            .filter(|(level, _)| *level == LogLevel::Error)
+            .count();
+        // Failures before compilation (missing target, bad manifest) only show up on stderr.
+        if errors == 0 {
+            let tail: Vec<&str> = build
+                .stderr
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .rev()
+                .take(10)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect();
+            Logger::new().log_message_with_trace(LogLevel::Error, "cargo build output:", tail);
+        }
+        let err = format!("cargo build failed for plugin ({} error(s))", errors);
+        Logger::new().log_message(LogLevel::Error, &err);
+        return Err(err);
+    }
+
+    let warnings = build
+        .diagnostics
+        .iter()
+        .filter(|(level, _)| *level == LogLevel::Warning)
+        .count();
+    let done = if warnings > 0 {
+        format!("Compilation finished with {} warning(s)", warnings)
+    } else {
+        "Compilation finished".to_string()
+    };
+    Logger::new().log_message(LogLevel::Success, &done);
+    Ok(build)
+}
+
+/// Maps a rustc diagnostic to a log level and its rendered text, skipping summary lines.
+fn into_diagnostic(msg: CompilerMessage) -> Option<(LogLevel, String)> {
+    // "aborting due to ..." and "N warnings emitted" carry no spans and repeat the counts.
+    if msg.spans.is_empty()
+        && (msg.message.starts_with("aborting due to") || msg.message.ends_with("emitted"))
+    {
+        return None;
+    }
+    let level = match msg.level.as_str() {
+        "error" | "error: internal compiler error" => LogLevel::Error,
+        "warning" => LogLevel::Warning,
+        _ => LogLevel::Info,
+    };
+    // The rendered text starts with "warning: <message>"; the log level already says that.
+    let text = match msg.rendered {
+        Some(rendered) => {
+            let body: Vec<&str> = rendered.lines().skip(1).collect();
+            format!("{}\n{}", msg.message, body.join("\n"))
+        }
+        None => msg.message,
+    };
+    Some((level, text))
+}
+
+fn log_diagnostics(diagnostics: &[(LogLevel, String)]) {
+    let logger = Logger::new();
+    for (level, rendered) in diagnostics {
+        let mut lines = rendered.trim_end().lines();
+        let head = lines.next().unwrap_or_default();
+        let trace: Vec<&str> = lines.collect();
+        logger.log_message_with_trace(level.clone(), head, trace);
+    }
+}
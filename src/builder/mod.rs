@@ -1,2 +1,6 @@
 pub mod bank;
+pub mod cache;
+pub mod cargo;
 pub mod plugin;
+pub mod readme;
+pub mod sign;
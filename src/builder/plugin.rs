@@ -1,4 +1,10 @@
+use crate::builder::{
+    cache::{self, BuildStatus},
+    cargo, readme,
+    sign::{self, SignatureOptions},
+};
 use crate::utils::{
+    engine::{self, EngineSection},
     fs as ufs,
     logger::{LogLevel, Logger},
     spinner,
@@ -7,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use wasmparser::{ExternalKind, Parser, Payload};
 
@@ -16,11 +22,13 @@ struct PluginSection {
     name: String,
     publisher: String,
     #[serde(default)]
-    _description: Option<String>,
+    description: Option<String>,
     #[serde(default)]
     version: Option<String>,
     #[serde(default)]
     access: Option<String>,
+    #[serde(default)]
+    engine: Option<EngineSection>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -34,14 +42,70 @@ struct PluginTomlDoc {
     plugin: PluginSection,
     #[serde(default)]
     exports: Vec<ExportEntryToml>,
+    #[serde(default)]
+    build: Option<BuildSection>,
+}
+
+/// `[build]` options of plugin.toml.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BuildSection {
+    /// Also produce `<publisher>.<name>.src.tar.gz`, as `--with-source` does
+    #[serde(default)]
+    include_source: bool,
+    /// Sign the archive on every build, as `--sign` does
+    #[serde(default)]
+    sign: bool,
+}
+
+/// Which binaries `plugin build` compiles and packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginTarget {
+    /// `wasm32-unknown-unknown` module, loadable by the playground and the CLI.
+    Wasm,
+    /// Native cdylib for the host platform.
+    Native,
+    /// Both binaries in the same archive.
+    All,
+}
+
+impl PluginTarget {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "wasm" => Ok(PluginTarget::Wasm),
+            "native" => Ok(PluginTarget::Native),
+            "all" => Ok(PluginTarget::All),
+            other => Err(format!(
+                "Unknown plugin target '{}'; expected native, wasm or all",
+                other
+            )),
+        }
+    }
+
+    fn includes_wasm(self) -> bool {
+        matches!(self, PluginTarget::Wasm | PluginTarget::All)
+    }
+
+    fn includes_native(self) -> bool {
+        matches!(self, PluginTarget::Native | PluginTarget::All)
+    }
+}
+
+/// A compiled binary placed in the plugin archive.
+struct PluginBinary {
+    kind: &'static str,
+    target: String,
+    archive_path: String,
+    bytes: Vec<u8>,
 }
 
 pub fn build_plugin(
     path: &str,
     release: &bool,
     cwd: &str,
-    require_signature: bool,
+    signature: SignatureOptions,
     show_summary: bool,
+    target: PluginTarget,
+    with_source: bool,
 ) -> Result<(), String> {
     let plugin_dir = spinner::run_step(
         &format!("Resolving plugin directory for '{}'", path),
@@ -104,25 +168,56 @@ pub fn build_plugin(
         },
     )?;
 
-    spinner::run_unit_step(
-        "Running cargo build (wasm32-unknown-unknown)",
-        "Compilation finished",
-        || {
-            let mut cmd = std::process::Command::new("cargo");
-            cmd.current_dir(&plugin_dir);
-            cmd.arg("build");
-            cmd.arg("--target");
-            cmd.arg("wasm32-unknown-unknown");
-            if *release {
-                cmd.arg("--release");
-            }
-            let status = cmd
-                .status()
-                .map_err(|e| format!("Failed to run cargo build: {}", e))?;
-            if !status.success() {
-                return Err(format!("cargo build failed for plugin: exit={}", status));
+    let wasm_artifact = if target.includes_wasm() {
+        let build = cargo::build_cdylib_step(
+            "Running cargo build (wasm32-unknown-unknown)",
+            &plugin_dir,
+            *release,
+            Some("wasm32-unknown-unknown"),
+        )?;
+        let path = build.artifact_with_suffix(".wasm").ok_or_else(|| {
+            "cargo did not report a .wasm artifact; is crate-type = [\"cdylib\"] set?".to_string()
+        })?;
+        Some(path.to_path_buf())
+    } else {
+        None
+    };
+
+    let native_artifact = if target.includes_native() {
+        let build = cargo::build_cdylib_step(
+            &format!("Running cargo build (native, {})", native_target_id()),
+            &plugin_dir,
+            *release,
+            None,
+        )?;
+        let path = build
+            .artifact_with_suffix(std::env::consts::DLL_SUFFIX)
+            .ok_or_else(|| {
+                format!(
+                    "cargo did not report a native {} artifact; is crate-type = [\"cdylib\"] set?",
+                    std::env::consts::DLL_SUFFIX
+                )
+            })?;
+        Some(path.to_path_buf())
+    } else {
+        None
+    };
+
+    // Cargo.lock exists once cargo has run, so the resolved version is known here.
+    let engine_section = spinner::run_step(
+        "Resolving devalang engine version",
+        |e: &EngineSection| match (&e.devalang, &e.devalang_resolved) {
+            (Some(req), Some(resolved)) => {
+                format!("Engine devalang {} (built with {})", req, resolved)
             }
-            Ok(())
+            (Some(req), None) => format!("Engine devalang {} (Cargo.lock not found)", req),
+            _ => "No devalang dependency found in Cargo.lock".to_string(),
+        },
+        || {
+            engine::resolve_plugin_engine(
+                plugin_doc.plugin.engine.clone(),
+                engine::locked_devalang_version(&plugin_dir),
+            )
         },
     )?;
 
@@ -139,37 +234,46 @@ pub fn build_plugin(
         ),
         "Archive created",
         || {
-            create_plugin_tar_gz_wasm_only(
-                &plugin_toml_path,
+            create_plugin_tar_gz(
                 &out_file,
                 &name,
                 &publisher,
-                plugin_doc.plugin._description.clone(),
                 &plugin_dir,
-                *release,
+                &engine_section,
+                wasm_artifact.as_deref(),
+                native_artifact.as_deref(),
             )
         },
     )?;
 
-    if require_signature {
-        // signature file uses the same base name and `.tar.gz.sig` suffix
-        let sig_path = out_root.join(format!("{}.{}.tar.gz.sig", publisher, name));
+    let include_source = with_source
+        || plugin_doc
+            .build
+            .as_ref()
+            .is_some_and(|build| build.include_source);
+    let src_file = out_root.join(format!("{}.{}.src.tar.gz", publisher, name));
+    if include_source {
         spinner::run_unit_step(
-            &format!("Checking signature at {}", sig_path.display()),
-            "Signature present",
-            || {
-                if sig_path.exists() {
-                    Ok(())
-                } else {
-                    Err(format!(
-                        "Signing required but signature file not found at {}",
-                        sig_path.display()
-                    ))
-                }
-            },
+            &format!(
+                "Packaging source archive {}",
+                src_file
+                    .file_name()
+                    .and_then(|f| f.to_str())
+                    .unwrap_or("archive")
+            ),
+            "Source archive created",
+            || create_plugin_source_tar_gz(&plugin_dir, &src_file),
         )?;
     }
 
+    let mut outputs = vec![out_file.clone()];
+    if include_source {
+        outputs.push(src_file.clone());
+    }
+
+    let manifest_sign = plugin_doc.build.as_ref().is_some_and(|build| build.sign);
+    outputs.extend(sign::sign_and_check(&out_file, signature, manifest_sign)?);
+
     if show_summary {
         Logger::new().log_message(
             LogLevel::Success,
@@ -182,11 +286,66 @@ pub fn build_plugin(
                 &format!("Failed to print summary: {}", e),
             );
         }
+        if include_source && let Err(e) = print_artifact_summary(&src_file) {
+            Logger::new().log_message(
+                LogLevel::Warning,
+                &format!("Failed to print source archive summary: {}", e),
+            );
+        }
+    }
+
+    // Fingerprint after the build: it rewrites plugin.toml and README.md
+    let options = cache_options(*release, target, with_source, signature);
+    if let Err(e) = plugin_input_files(&plugin_dir)
+        .and_then(|inputs| cache::record(cwd, "plugin", &plugin_dir, &inputs, &options, &outputs))
+    {
+        Logger::new().log_message(
+            LogLevel::Warning,
+            &format!("Failed to update the build cache: {}", e),
+        );
     }
 
     Ok(())
 }
-pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) -> Result<(), String> {
+
+/// Build options that change a plugin's artifacts, as recorded in the build cache.
+fn cache_options(
+    release: bool,
+    target: PluginTarget,
+    with_source: bool,
+    signature: SignatureOptions,
+) -> String {
+    format!(
+        "release={} target={:?} with_source={} {}",
+        release,
+        target,
+        with_source,
+        signature.cache_key()
+    )
+}
+
+/// Builds all plugins under generated/plugins.
+///
+/// Plugins unchanged since their last build with the same options are skipped.
+///
+/// ### Parameters
+/// - `release`: Whether to build the release version
+/// - `cwd`: The current working directory
+/// - `signature`: Whether to sign each archive and whether a valid signature is required
+/// - `target`: The binaries to build
+/// - `with_source`: Whether to also package the sources
+/// - `force`: Rebuild every plugin, ignoring the build cache
+/// - `jobs`: The maximum number of plugins built at once; cargo builds share one jobserver
+///
+pub fn build_all_plugins(
+    release: &bool,
+    cwd: &str,
+    signature: SignatureOptions,
+    target: PluginTarget,
+    with_source: bool,
+    force: bool,
+    jobs: usize,
+) -> Result<(), String> {
     let plugins_root = Path::new(cwd).join("generated").join("plugins");
     if !plugins_root.exists() {
         return Err(format!(
@@ -217,22 +376,62 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
 
     let mut errors: Vec<String> = Vec::new();
     let mut successes: Vec<String> = Vec::new();
+    let mut cached: Vec<String> = Vec::new();
     let total = dirs.len();
-    for p in dirs {
+    let options = cache_options(*release, target, with_source, signature);
+    let build_one = |p: PathBuf| {
         let p_str = p.to_string_lossy().to_string();
-        match build_plugin(&p_str, release, cwd, require_signature, true) {
-            Ok(_) => successes.push(p_str.clone()),
+        if !force
+            && plugin_input_files(&p)
+                .map(|inputs| cache::is_fresh(cwd, "plugin", &p, &inputs, &options))
+                .unwrap_or(false)
+        {
+            Logger::new().log_message(LogLevel::Info, &format!("Cached (unchanged): {}", p_str));
+            return (p_str, Ok(BuildStatus::Cached));
+        }
+        let result = build_plugin(&p_str, release, cwd, signature, true, target, with_source)
+            .map(|_| BuildStatus::Built);
+        (p_str, result)
+    };
+    let label = |p: &PathBuf| {
+        ufs::path_relative_to(p, &plugins_root)
+            .map(ufs::to_unix_string)
+            .unwrap_or_else(|| p.to_string_lossy().to_string())
+    };
+    // Unlock the key up front: a passphrase prompt cannot share the terminal with spinners
+    if signature.sign
+        || dirs
+            .iter()
+            .any(|d| sign::manifest_requests_signing(&d.join("plugin.toml")))
+    {
+        sign::prepare_key()?;
+    }
+    let results = if jobs > 1 {
+        cargo::with_shared_jobserver(jobs, || spinner::run_parallel(dirs, jobs, label, build_one))
+    } else {
+        spinner::run_parallel(dirs, jobs, label, build_one)
+    };
+    for (p_str, result) in results {
+        match result {
+            Ok(BuildStatus::Built) => successes.push(p_str),
+            Ok(BuildStatus::Cached) => cached.push(p_str),
             Err(e) => errors.push(format!("{} -> {}", p_str, e)),
         }
     }
 
     // Summary info
-    Logger::new().log_message(LogLevel::Info, &format!("{} addons built", successes.len()));
+    Logger::new().log_message(
+        LogLevel::Info,
+        &format!("{} addons built, {} cached", successes.len(), cached.len()),
+    );
 
     let mut trace_lines: Vec<String> = Vec::new();
     for s in &successes {
         trace_lines.push(format!("Built: {}", s));
     }
+    for c in &cached {
+        trace_lines.push(format!("Cached: {}", c));
+    }
     for e in &errors {
         trace_lines.push(format!("Failed: {}", e));
     }
@@ -242,7 +441,11 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
     if errors.is_empty() {
         Logger::new().log_message(
             LogLevel::Success,
-            &format!("Build complete: {} plugin(s) built", total),
+            &format!(
+                "Build complete: {} plugin(s) built, {} cached",
+                successes.len(),
+                cached.len()
+            ),
         );
         Ok(())
     } else {
@@ -256,7 +459,7 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
     }
 }
 
-fn resolve_plugin_dir(cwd: &str, input: &str) -> Result<PathBuf, String> {
+pub fn resolve_plugin_dir(cwd: &str, input: &str) -> Result<PathBuf, String> {
     let candidate = Path::new(cwd).join(input);
     if candidate.is_file()
         && candidate
@@ -324,106 +527,10 @@ fn resolve_plugin_dir(cwd: &str, input: &str) -> Result<PathBuf, String> {
     ))
 }
 
-#[allow(dead_code)]
-fn create_plugin_zip(
-    plugin_toml_path: &Path,
-    out_zip: &Path,
-    name: &str,
-    publisher: &str,
-    description: Option<String>,
-    plugin_dir: &Path,
-) -> Result<(), String> {
-    let file =
-        fs::File::create(out_zip).map_err(|e| format!("Failed to create output file: {}", e))?;
-    let mut zip = zip::ZipWriter::new(file);
-    let options =
-        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-    // Add plugin.toml at root
-    zip.start_file("plugin.toml", options)
-        .map_err(|e| format!("Failed to zip plugin.toml: {}", e))?;
-    let mut toml_bytes = Vec::new();
-    fs::File::open(plugin_toml_path)
-        .and_then(|mut f| f.read_to_end(&mut toml_bytes))
-        .map_err(|e| format!("Failed to read plugin.toml: {}", e))?;
-    zip.write_all(&toml_bytes)
-        .map_err(|e| format!("Failed to write plugin.toml to zip: {}", e))?;
-
-    // Add README.md (from plugin dir if present, else default)
-    zip.start_file("README.md", options)
-        .map_err(|e| format!("Failed to add README.md: {}", e))?;
-    let readme_path = plugin_dir.join("README.md");
-    if readme_path.exists() {
-        let mut buf = Vec::new();
-        fs::File::open(&readme_path)
-            .and_then(|mut f| f.read_to_end(&mut buf))
-            .map_err(|e| format!("Failed to read README.md: {}", e))?;
-        zip.write_all(&buf)
-            .map_err(|e| format!("Failed to write README.md: {}", e))?;
-    } else {
-        let readme = default_readme_plugin(publisher, name, description.as_deref());
-        zip.write_all(readme.as_bytes())
-            .map_err(|e| format!("Failed to write README.md: {}", e))?;
-    }
-
-    // Add LICENSE (from plugin dir if present, else default MIT)
-    zip.start_file("LICENSE", options)
-        .map_err(|e| format!("Failed to add LICENSE: {}", e))?;
-    let license_path = plugin_dir.join("LICENSE");
-    if license_path.exists() {
-        let mut buf = Vec::new();
-        fs::File::open(&license_path)
-            .and_then(|mut f| f.read_to_end(&mut buf))
-            .map_err(|e| format!("Failed to read LICENSE: {}", e))?;
-        zip.write_all(&buf)
-            .map_err(|e| format!("Failed to write LICENSE: {}", e))?;
-    } else {
-        let license = default_mit_license(publisher);
-        zip.write_all(license.as_bytes())
-            .map_err(|e| format!("Failed to write LICENSE: {}", e))?;
-    }
-
-    // Add source tree: Cargo.toml, src/, and any other files in plugin_dir except target/
-    let files = ufs::walk_files(plugin_dir)?;
-    for p in files {
-        if !p.is_file() {
-            continue;
-        }
-        // Skip target directory files
-        if p.components().any(|c| c.as_os_str() == "target") {
-            continue;
-        }
-        // Compute path relative to plugin_dir and write under `source/` in the zip
-        let rel_os = ufs::path_relative_to(&p, plugin_dir).unwrap_or_else(|| {
-            p.file_name()
-                .map(PathBuf::from)
-                .unwrap_or_else(PathBuf::new)
-        });
-        let rel = ufs::to_unix_string(&rel_os);
-        let mut data = Vec::new();
-        fs::File::open(&p)
-            .and_then(|mut f| f.read_to_end(&mut data))
-            .map_err(|e| format!("Failed to read file: {}", e))?;
-        // Write files directly at the archive root while preserving relative paths
-        // (e.g. `src/lib.rs` will appear as `src/lib.rs` at the archive root).
-        let zip_path = rel.clone();
-        zip.start_file(zip_path.clone(), options)
-            .map_err(|e| format!("Failed to add {}: {}", zip_path, e))?;
-        zip.write_all(&data)
-            .map_err(|e| format!("Failed to write {}: {}", zip_path, e))?;
-    }
-
-    zip.finish()
-        .map_err(|e| format!("Failed to finalize zip: {}", e))?;
-    let _ = fs::metadata(out_zip).map_err(|e| format!("Failed to stat zip: {}", e))?;
-    Ok(())
-}
-
-#[allow(dead_code)]
 fn default_readme_plugin(publisher: &str, name: &str, description: Option<&str>) -> String {
     let desc = description.unwrap_or("Plugin for Devalang.");
     format!(
-        "# {}.{} Plugin\n\n{}\n\nContents:\n- plugin.toml\n- sources (placed at archive root, e.g. src/..., Cargo.toml)\n- LICENSE\n\nBuilt with devapack.\n",
+        "# {}.{} Plugin\n\n{}\n\nContents:\n- plugin.toml\n- compiled binaries (<name>.wasm, native/)\n- LICENSE\n\nBuilt with devapack.\n",
         publisher, name, desc
     )
 }
@@ -435,294 +542,190 @@ fn default_mit_license(publisher: &str) -> String {
     )
 }
 
-#[allow(dead_code)]
-fn create_plugin_tar_gz_native(
-    plugin_toml_path: &Path,
-    out_zip: &Path,
-    name: &str,
-    publisher: &str,
-    _description: Option<String>,
-    plugin_dir: &Path,
-    release: bool,
-) -> Result<(), String> {
-    // Localiser la bibliothèque native (DLL sur Windows, SO sur Linux, DYLIB sur macOS)
-    let profile = if release { "release" } else { "debug" };
-
-    // Déterminer l'extension selon la plateforme
-    #[cfg(target_os = "windows")]
-    let lib_ext = "dll";
-    #[cfg(target_os = "linux")]
-    let lib_ext = "so";
-    #[cfg(target_os = "macos")]
-    let lib_ext = "dylib";
-
-    // Sur certaines plateformes, la bibliothèque peut avoir le préfixe "lib"
-    #[cfg(any(target_os = "linux", target_os = "macos"))]
-    let lib_prefix = "lib";
-    #[cfg(target_os = "windows")]
-    let lib_prefix = "";
-
-    let lib_name = format!("{}{}.{}", lib_prefix, name.replace('-', "_"), lib_ext);
-    let lib_path = plugin_dir.join("target").join(profile).join(&lib_name);
-
-    if !lib_path.exists() {
-        return Err(format!(
-            "Native library not found: {}",
-            lib_path.to_string_lossy()
-        ));
-    }
-
-    // Si un ancien artifact existe, le supprimer pour éviter les erreurs de verrouillage Windows
-    if out_zip.exists() {
-        fs::remove_file(out_zip)
-            .map_err(|e| format!("Failed to remove existing output file: {}", e))?;
-    }
-
-    // Lire le plugin.toml original pour préserver les métadonnées [plugin]
-    let toml_txt = fs::read_to_string(plugin_toml_path)
-        .map_err(|e| format!("Failed to read plugin.toml: {}", e))?;
-
-    let plugin_doc: Option<PluginTomlDoc> = toml::from_str(&toml_txt).ok();
+/// Identifier of the host platform used for native binaries, e.g. `x86_64-linux`.
+fn native_target_id() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
 
-    // Scanner les sources du plugin pour les macros export_plugin!(name, ...)
+/// Scans plugin sources for `export_plugin!(name, ...)`, `export_plugin_ext!(name, ...)`
+/// and `export_plugin_with_state!(name, ...)` macros.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin crate directory
+///
+fn scan_attribute_exports(plugin_dir: &Path) -> Vec<String> {
     let mut attribute_exports: Vec<String> = Vec::new();
     let src_root = plugin_dir.join("src");
-    if src_root.exists() {
-        if let Ok(files) = ufs::walk_files(&src_root) {
-            for f in files {
-                if !f.is_file() {
-                    continue;
-                }
-                if let Some(ext) = f.extension().and_then(|s| s.to_str()) {
-                    if ext != "rs" {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-                if let Ok(s) = fs::read_to_string(&f) {
-                    // Chercher export_plugin!(name, ...) ou export_plugin_ext!(name, ...)
-                    let mut pos = 0usize;
-                    while let Some(idx) = s[pos..].find("export_plugin") {
-                        let start = pos + idx + "export_plugin".len();
-                        // Vérifier si c'est export_plugin! ou export_plugin_ext!
-                        let rest = &s[start..];
-                        if !rest.starts_with("!") && !rest.starts_with("_ext!") {
-                            pos = start;
-                            continue;
-                        }
+    let Ok(files) = ufs::walk_files(&src_root) else {
+        return attribute_exports;
+    };
+    for f in files {
+        if !f.is_file() || f.extension().and_then(|s| s.to_str()) != Some("rs") {
+            continue;
+        }
+        let Ok(s) = fs::read_to_string(&f) else {
+            continue;
+        };
+        let mut pos = 0usize;
+        while let Some(idx) = s[pos..].find("export_plugin") {
+            let start = pos + idx + "export_plugin".len();
+            let rest = &s[start..];
+            if !rest.starts_with("!")
+                && !rest.starts_with("_ext!")
+                && !rest.starts_with("_with_state!")
+            {
+                pos = start;
+                continue;
+            }
 
-                        // Sauter jusqu'à la parenthèse ouvrante
-                        if let Some(paren_idx) = s[start..].find('(') {
-                            let name_start = start + paren_idx + 1;
-                            // Trouver la virgule ou la parenthèse fermante
-                            if let Some(comma_idx) = s[name_start..].find(',') {
-                                let name = s[name_start..name_start + comma_idx].trim();
-                                if !name.is_empty() {
-                                    attribute_exports.push(name.to_string());
-                                }
-                                pos = name_start + comma_idx;
-                            } else {
-                                break;
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
+            // Skip to opening parenthesis, then read up to the first comma
+            let Some(paren_idx) = s[start..].find('(') else {
+                break;
+            };
+            let name_start = start + paren_idx + 1;
+            let Some(comma_idx) = s[name_start..].find(',') else {
+                break;
+            };
+            let name = s[name_start..name_start + comma_idx].trim();
+            if !name.is_empty() {
+                attribute_exports.push(name.to_string());
             }
+            pos = name_start + comma_idx;
         }
     }
-
-    // Dédupliquer et trier pour une sortie stable
     attribute_exports.sort();
     attribute_exports.dedup();
+    attribute_exports
+}
 
-    // Reconstruire le contenu plugin.toml : conserver la section [plugin] et remplacer exports
-    let mut out_toml = String::new();
-    if let Some(doc) = plugin_doc {
-        // Construire l'en-tête du plugin
-        out_toml.push_str("[plugin]\n");
-        out_toml.push_str(&format!("name = \"{}\"\n", doc.plugin.name));
-        out_toml.push_str(&format!("publisher = \"{}\"\n", doc.plugin.publisher));
-        if let Some(d) = doc.plugin._description {
-            out_toml.push_str(&format!("description = \"{}\"\n", d));
-        }
-        if let Some(v) = doc.plugin.version {
-            out_toml.push_str(&format!("version = \"{}\"\n", v));
-        }
-        if let Some(a) = doc.plugin.access {
-            out_toml.push_str(&format!("access = \"{}\"\n", a));
-        }
-    } else {
-        // Fallback : écrire les lignes d'en-tête plugin.toml originales
-        if let Some(idx) = toml_txt.find("[[exports]]") {
-            out_toml.push_str(&toml_txt[..idx]);
-        } else {
-            out_toml.push_str(&toml_txt);
-        }
-    }
-
-    for name_export in attribute_exports {
-        out_toml.push_str("\n[[exports]]\n");
-        out_toml.push_str(&format!("name = \"{}\"\nkind = \"func\"\n", name_export));
-    }
-
-    // Réécrire le plugin.toml source pour que generated/plugins/<publisher>/<name>/plugin.toml
-    // soit mis à jour pour refléter les exports reconstruits
-    fs::write(plugin_toml_path, &out_toml)
-        .map_err(|e| format!("Failed to write plugin.toml back to source: {}", e))?;
-
-    use flate2::{Compression, write::GzEncoder};
-    use std::fs::File;
-    use tar::Builder;
-
-    let f = File::create(out_zip).map_err(|e| format!("Failed to create output file: {}", e))?;
-    let enc = GzEncoder::new(f, Compression::default());
-    let mut tar = Builder::new(enc);
-
-    // plugin.toml
-    tar.append_path_with_name(plugin_toml_path, "plugin.toml")
-        .map_err(|e| format!("Failed to add plugin.toml to tar: {}", e))?;
-
-    // LICENSE
-    let license_path = plugin_dir.join("LICENSE");
-    if license_path.exists() {
-        tar.append_path_with_name(&license_path, "LICENSE")
-            .map_err(|e| format!("Failed to add LICENSE to tar: {}", e))?;
-    } else {
-        let license = default_mit_license(publisher);
-        let mut header = tar::Header::new_gnu();
-        header.set_size(license.len() as u64);
-        header.set_cksum();
-        tar.append_data(&mut header, "LICENSE", license.as_bytes())
-            .map_err(|e| format!("Failed to append LICENSE data: {}", e))?;
-    }
-
-    // Bibliothèque native à la racine
-    tar.append_path_with_name(&lib_path, &lib_name)
-        .map_err(|e| format!("Failed to add native library to tar: {}", e))?;
-
-    tar.finish()
-        .map_err(|e| format!("Failed to finalize tar: {}", e))?;
-    Ok(())
+/// Whether an export is a parameter setter: `set_<param>` written by hand, or the
+/// `set<Param>` functions generated by `export_plugin_with_state!`.
+fn is_setter_export(name: &str) -> bool {
+    name.starts_with("set_")
+        || name
+            .strip_prefix("set")
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| c.is_ascii_uppercase())
 }
 
-#[allow(dead_code)]
-fn create_plugin_tar_gz_wasm_only(
-    plugin_toml_path: &Path,
-    out_zip: &Path,
+/// Packages the compiled binaries into `<publisher>.<name>.tar.gz`.
+///
+/// The WASM module is placed at the archive root as `<name>.wasm`; the native library
+/// goes under `native/<arch>-<os>/`. Every included binary is recorded as a `[[binaries]]`
+/// entry in plugin.toml so consumers can pick the one they can load.
+///
+/// ### Parameters
+/// - `out_file`: The archive to create
+/// - `name`: The plugin name
+/// - `publisher`: The plugin publisher
+/// - `plugin_dir`: The plugin crate directory; its README.md section is refreshed and its
+///   plugin.toml is rewritten with the detected exports, engine and binaries
+/// - `engine_section`: The engine requirement and resolved devalang version to record
+/// - `wasm_artifact`: The `.wasm` file reported by cargo, if built
+/// - `native_artifact`: The native library reported by cargo, if built
+///
+fn create_plugin_tar_gz(
+    out_file: &Path,
     name: &str,
     publisher: &str,
-    _description: Option<String>,
     plugin_dir: &Path,
-    release: bool,
+    engine_section: &EngineSection,
+    wasm_artifact: Option<&Path>,
+    native_artifact: Option<&Path>,
 ) -> Result<(), String> {
-    // locate wasm artifact
-    let profile = if release { "release" } else { "debug" };
-    let wasm_path = plugin_dir
-        .join("target")
-        .join("wasm32-unknown-unknown")
-        .join(profile)
-        .join(format!("{}.wasm", name));
-    if !wasm_path.exists() {
-        return Err(format!(
-            "WASM artifact not found: {}",
-            wasm_path.to_string_lossy()
-        ));
+    let plugin_toml_path = &plugin_dir.join("plugin.toml");
+    let mut binaries: Vec<PluginBinary> = Vec::new();
+
+    if let Some(wasm_path) = wasm_artifact {
+        let bytes = fs::read(wasm_path)
+            .map_err(|e| format!("Failed to read wasm {}: {}", wasm_path.to_string_lossy(), e))?;
+        binaries.push(PluginBinary {
+            kind: "wasm",
+            target: "wasm32-unknown-unknown".to_string(),
+            archive_path: format!("{}.wasm", name),
+            bytes,
+        });
+    }
+
+    if let Some(lib_path) = native_artifact {
+        let lib_name = lib_path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| format!("Invalid native library path: {}", lib_path.display()))?;
+        let bytes = fs::read(lib_path).map_err(|e| {
+            format!(
+                "Failed to read native library {}: {}",
+                lib_path.to_string_lossy(),
+                e
+            )
+        })?;
+        let target_id = native_target_id();
+        binaries.push(PluginBinary {
+            kind: "native",
+            archive_path: format!("native/{}/{}", target_id, lib_name),
+            target: target_id,
+            bytes,
+        });
     }
 
     // If an old artifact exists, remove it first to avoid Windows file-lock errors.
-    if out_zip.exists() {
-        fs::remove_file(out_zip)
+    if out_file.exists() {
+        fs::remove_file(out_file)
             .map_err(|e| format!("Failed to remove existing output file: {}", e))?;
     }
 
-    // Read wasm bytes early so we can detect exported symbols and build plugin.toml
-    let mut wasm_bytes = Vec::new();
-    fs::File::open(&wasm_path)
-        .and_then(|mut f| f.read_to_end(&mut wasm_bytes))
-        .map_err(|e| format!("Failed to read wasm: {}", e))?;
-
-    // Read and parse original plugin.toml to preserve [plugin] metadata.
+    // Read and parse original plugin.toml to preserve [plugin] metadata and [build] options.
     let toml_txt = fs::read_to_string(plugin_toml_path)
         .map_err(|e| format!("Failed to read plugin.toml: {}", e))?;
 
     let plugin_doc: Option<PluginTomlDoc> = toml::from_str(&toml_txt).ok();
 
-    // Scan plugin sources for export_plugin!(name, ...) or export_plugin_ext!(name, ...) macros
-    let mut attribute_exports: Vec<String> = Vec::new();
-    let src_root = plugin_dir.join("src");
-    if src_root.exists() {
-        if let Ok(files) = ufs::walk_files(&src_root) {
-            for f in files {
-                if !f.is_file() {
-                    continue;
-                }
-                if let Some(ext) = f.extension().and_then(|s| s.to_str()) {
-                    if ext != "rs" {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-                if let Ok(s) = fs::read_to_string(&f) {
-                    // Search for export_plugin!(name, ...) or export_plugin_ext!(name, ...) or export_plugin_with_state!(name, ...)
-                    let mut pos = 0usize;
-                    while let Some(idx) = s[pos..].find("export_plugin") {
-                        let start = pos + idx + "export_plugin".len();
-                        // Check if it's export_plugin! or export_plugin_ext! or export_plugin_with_state!
-                        let rest = &s[start..];
-                        if !rest.starts_with("!")
-                            && !rest.starts_with("_ext!")
-                            && !rest.starts_with("_with_state!")
-                        {
-                            pos = start;
-                            continue;
-                        }
-
-                        // Skip to opening parenthesis
-                        if let Some(paren_idx) = s[start..].find('(') {
-                            let name_start = start + paren_idx + 1;
-                            // Find comma or closing paren
-                            if let Some(comma_idx) = s[name_start..].find(',') {
-                                let name = s[name_start..name_start + comma_idx].trim();
-                                if !name.is_empty() {
-                                    attribute_exports.push(name.to_string());
-                                }
-                                pos = name_start + comma_idx;
-                            } else {
-                                break;
+    let attribute_exports = scan_attribute_exports(plugin_dir);
+
+    // Prefer the exports actually present in the wasm module; native libraries are not
+    // inspected, so a native-only build falls back to the macro declarations.
+    let mut exported_funcs: Vec<String> = match binaries.iter().find(|b| b.kind == "wasm") {
+        Some(wasm) => {
+            let mut funcs = Vec::new();
+            for payload in Parser::new(0).parse_all(&wasm.bytes).flatten() {
+                if let Payload::ExportSection(reader) = payload {
+                    for exp in reader.into_iter().flatten() {
+                        if exp.kind == ExternalKind::Func {
+                            let name = exp.name.to_string();
+                            // include setters and any names declared via attribute
+                            if is_setter_export(&name)
+                                || attribute_exports.iter().any(|a| a == &name)
+                            {
+                                funcs.push(name);
                             }
-                        } else {
-                            break;
                         }
                     }
                 }
             }
+            funcs
         }
-    }
-
-    // Parse wasm exports and collect relevant exported function names
-    let mut exported_funcs: Vec<String> = Vec::new();
-    for payload in Parser::new(0).parse_all(&wasm_bytes).flatten() {
-        if let Payload::ExportSection(reader) = payload {
-            for exp in reader.into_iter().flatten() {
-                if exp.kind == ExternalKind::Func {
-                    let name = exp.name.to_string();
-                    // include setters and any names declared via attribute
-                    if name.starts_with("set_") || attribute_exports.iter().any(|a| a == &name) {
-                        exported_funcs.push(name);
-                    }
-                }
-            }
-        }
-    }
+        None => attribute_exports,
+    };
 
     // Deduplicate and sort for stable output
     exported_funcs.sort();
     exported_funcs.dedup();
 
+    // Refresh the generated README section; hand-written text outside the markers is kept
+    let description = plugin_doc
+        .as_ref()
+        .and_then(|doc| doc.plugin.description.clone());
+    readme::refresh_readme(
+        &plugin_dir.join("README.md"),
+        &readme::plugin_section(
+            publisher,
+            name,
+            engine_section.devalang.as_deref(),
+            &exported_funcs,
+        ),
+        &default_readme_plugin(publisher, name, description.as_deref()),
+    )?;
+
     // Rebuild plugin.toml content: keep [plugin] section and replace exports with the detected ones
     let mut out_toml = String::new();
     if let Some(doc) = plugin_doc {
@@ -730,7 +733,7 @@ fn create_plugin_tar_gz_wasm_only(
         out_toml.push_str("[plugin]\n");
         out_toml.push_str(&format!("name = \"{}\"\n", doc.plugin.name));
         out_toml.push_str(&format!("publisher = \"{}\"\n", doc.plugin.publisher));
-        if let Some(d) = doc.plugin._description {
+        if let Some(d) = doc.plugin.description {
             out_toml.push_str(&format!("description = \"{}\"\n", d));
         }
         if let Some(v) = doc.plugin.version {
@@ -739,13 +742,29 @@ fn create_plugin_tar_gz_wasm_only(
         if let Some(a) = doc.plugin.access {
             out_toml.push_str(&format!("access = \"{}\"\n", a));
         }
+        if engine_section.devalang.is_some() || engine_section.devalang_resolved.is_some() {
+            out_toml.push_str("\n[plugin.engine]\n");
+            if let Some(req) = &engine_section.devalang {
+                out_toml.push_str(&format!("devalang = \"{}\"\n", req));
+            }
+            if let Some(resolved) = &engine_section.devalang_resolved {
+                out_toml.push_str(&format!("devalang_resolved = \"{}\"\n", resolved));
+            }
+        }
+        if let Some(build) = doc.build {
+            let body = toml::to_string(&build)
+                .map_err(|e| format!("Failed to serialize [build]: {}", e))?;
+            out_toml.push_str("\n[build]\n");
+            out_toml.push_str(&body);
+        }
     } else {
         // Fallback: write original plugin.toml header lines (up to first [[exports]] or EOF)
-        if let Some(idx) = toml_txt.find("[[exports]]") {
-            out_toml.push_str(&toml_txt[..idx]);
-        } else {
-            out_toml.push_str(&toml_txt);
-        }
+        let end = ["[[exports]]", "[[binaries]]"]
+            .iter()
+            .filter_map(|marker| toml_txt.find(marker))
+            .min()
+            .unwrap_or(toml_txt.len());
+        out_toml.push_str(&toml_txt[..end]);
     }
 
     for name in exported_funcs {
@@ -753,6 +772,17 @@ fn create_plugin_tar_gz_wasm_only(
         out_toml.push_str(&format!("name = \"{}\"\nkind = \"func\"\n", name));
     }
 
+    for bin in &binaries {
+        out_toml.push_str("\n[[binaries]]\n");
+        out_toml.push_str(&format!(
+            "kind = \"{}\"\ntarget = \"{}\"\npath = \"{}\"\nsha256 = \"{}\"\n",
+            bin.kind,
+            bin.target,
+            bin.archive_path,
+            hex::encode(Sha256::digest(&bin.bytes))
+        ));
+    }
+
     // Overwrite the source plugin.toml so generated/plugins/<publisher>/<name>/plugin.toml
     // is updated to reflect the reconstructed exports.
     fs::write(plugin_toml_path, &out_toml)
@@ -762,7 +792,7 @@ fn create_plugin_tar_gz_wasm_only(
     use std::fs::File;
     use tar::Builder;
 
-    let f = File::create(out_zip).map_err(|e| format!("Failed to create output file: {}", e))?;
+    let f = File::create(out_file).map_err(|e| format!("Failed to create output file: {}", e))?;
     let enc = GzEncoder::new(f, Compression::default());
     let mut tar = Builder::new(enc);
 
@@ -770,6 +800,10 @@ fn create_plugin_tar_gz_wasm_only(
     tar.append_path_with_name(plugin_toml_path, "plugin.toml")
         .map_err(|e| format!("Failed to add plugin.toml to tar: {}", e))?;
 
+    // README.md
+    tar.append_path_with_name(plugin_dir.join("README.md"), "README.md")
+        .map_err(|e| format!("Failed to add README.md to tar: {}", e))?;
+
     // LICENSE
     let license_path = plugin_dir.join("LICENSE");
     if license_path.exists() {
@@ -784,19 +818,72 @@ fn create_plugin_tar_gz_wasm_only(
             .map_err(|e| format!("Failed to append LICENSE data: {}", e))?;
     }
 
-    // wasm artifact at root
-    let wasm_name = format!("{}.wasm", name);
-    let mut header = tar::Header::new_gnu();
-    header.set_size(wasm_bytes.len() as u64);
-    header.set_cksum();
-    tar.append_data(&mut header, &wasm_name, &wasm_bytes[..])
-        .map_err(|e| format!("Failed to append wasm data: {}", e))?;
+    // compiled binaries
+    for bin in &binaries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bin.bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, &bin.archive_path, &bin.bytes[..])
+            .map_err(|e| format!("Failed to append {}: {}", bin.archive_path, e))?;
+    }
 
     tar.finish()
         .map_err(|e| format!("Failed to finalize tar: {}", e))?;
     Ok(())
 }
 
+/// Files a plugin build reads: the plugin directory without ignored directories, plus the
+/// nearest Cargo.lock, listed as `Cargo.lock` when it lives outside the plugin directory.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin crate directory
+///
+fn plugin_input_files(plugin_dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files = ufs::addon_files(plugin_dir)?;
+    if !files.iter().any(|(rel, _)| rel == "Cargo.lock")
+        && let Some(lock) = ufs::find_in_ancestors(plugin_dir, "Cargo.lock")
+    {
+        files.push(("Cargo.lock".to_string(), lock));
+    }
+    Ok(files)
+}
+
+/// Packages the plugin sources into `<publisher>.<name>.src.tar.gz` so the published
+/// binaries can be audited and rebuilt.
+///
+/// Skips the same directories as the submit upload (`target`, `build`, `.git`, ...).
+/// The nearest Cargo.lock is added at the archive root when it lives outside the plugin
+/// directory, as in the shared plugins workspace.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin crate directory
+/// - `out_file`: The archive to create
+///
+fn create_plugin_source_tar_gz(plugin_dir: &Path, out_file: &Path) -> Result<(), String> {
+    use flate2::{Compression, GzBuilder};
+    use tar::Builder;
+
+    let mut files = plugin_input_files(plugin_dir)?;
+    // Sorted entries keep the archive stable between builds of the same sources
+    files.sort();
+
+    let f = fs::File::create(out_file)
+        .map_err(|e| format!("Failed to create source archive: {}", e))?;
+    let enc = GzBuilder::new().mtime(0).write(f, Compression::default());
+    let mut tar = Builder::new(enc);
+    for (rel, path) in &files {
+        tar.append_path_with_name(path, rel)
+            .map_err(|e| format!("Failed to add {} to source archive: {}", rel, e))?;
+    }
+    let enc = tar
+        .into_inner()
+        .map_err(|e| format!("Failed to finish source archive: {}", e))?;
+    enc.finish()
+        .map_err(|e| format!("Failed to finish gzip: {}", e))?;
+    Ok(())
+}
+
 fn print_artifact_summary(path: &Path) -> Result<(), String> {
     use std::fs::File;
     // compute size
@@ -823,6 +910,6 @@ fn print_artifact_summary(path: &Path) -> Result<(), String> {
         }
     });
 
-    crate::addon::summary::print_addon_summary(&payload, Path::new("local"));
+    crate::addon::summary::print_addon_summary(&payload, "local");
     Ok(())
 }
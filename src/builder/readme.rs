@@ -0,0 +1,281 @@
+use crate::utils::audio::AudioInfo;
+use std::fs;
+use std::path::Path;
+
+/// Opening marker of the README block devapack regenerates on every build.
+pub const BEGIN_MARKER: &str =
+    "<!-- devapack:begin (generated on build, edits inside this block are overwritten) -->";
+/// Closing marker of the generated README block.
+pub const END_MARKER: &str = "<!-- devapack:end -->";
+
+/// A bank trigger as listed in the README.
+#[derive(Debug, Clone)]
+pub struct TriggerDoc {
+    pub name: String,
+    /// Path relative to the bank root, e.g. `audio/kick.wav`
+    pub path: String,
+    pub audio: Option<AudioInfo>,
+}
+
+/// Wraps generated content in the begin/end markers.
+///
+/// ### Parameters
+/// - `content`: The Markdown to place between the markers
+///
+pub fn generated_block(content: &str) -> String {
+    format!("{}\n{}\n{}\n", BEGIN_MARKER, content.trim_end(), END_MARKER)
+}
+
+/// Replaces the generated block of a README, leaving everything outside the markers as is.
+///
+/// A README without markers gets the block appended; a missing README is created from
+/// `default_header` followed by the block. Unbalanced markers are an error, as there is
+/// no telling where the generated part ends.
+///
+/// ### Parameters
+/// - `readme_path`: The README.md to refresh
+/// - `content`: The generated Markdown
+/// - `default_header`: The hand-written part used when the README does not exist yet
+///
+pub fn refresh_readme(
+    readme_path: &Path,
+    content: &str,
+    default_header: &str,
+) -> Result<(), String> {
+    let block = generated_block(content);
+    let updated = if readme_path.exists() {
+        let current = fs::read_to_string(readme_path)
+            .map_err(|e| format!("Failed to read {}: {}", readme_path.display(), e))?;
+        replace_block(&current, &block).map_err(|e| format!("{}: {}", readme_path.display(), e))?
+    } else {
+        format!("{}\n\n{}", default_header.trim_end(), block)
+    };
+    fs::write(readme_path, updated)
+        .map_err(|e| format!("Failed to write {}: {}", readme_path.display(), e))
+}
+
+/// Swaps the first marked block of `readme` for `block`, or appends `block`.
+///
+/// The block takes the line endings of `readme` when it uses CRLF.
+fn replace_block(readme: &str, block: &str) -> Result<String, String> {
+    let newline = if readme.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let block = block.replace('\n', newline);
+    let begins = readme.matches(BEGIN_MARKER).count();
+    let ends = readme.matches(END_MARKER).count();
+    if begins == 0 && ends == 0 {
+        let mut out = readme.trim_end().to_string();
+        if !out.is_empty() {
+            out.push_str(newline);
+            out.push_str(newline);
+        }
+        out.push_str(&block);
+        return Ok(out);
+    }
+
+    let start = readme.find(BEGIN_MARKER).filter(|_| begins == ends);
+    let end_rel = start.and_then(|start| readme[start..].find(END_MARKER));
+    let (Some(start), Some(end_rel)) = (start, end_rel) else {
+        return Err(format!(
+            "unbalanced devapack markers ({} begin, {} end); fix them so the generated block can be found",
+            begins, ends
+        ));
+    };
+    let mut end = start + end_rel + END_MARKER.len();
+    if readme[end..].starts_with("\r\n") {
+        end += 2;
+    } else if readme[end..].starts_with('\n') {
+        end += 1;
+    }
+    Ok(format!("{}{}{}", &readme[..start], block, &readme[end..]))
+}
+
+/// Generated README content for a bank: its triggers and the `.devalang` snippet to use it.
+///
+/// ### Parameters
+/// - `publisher`: The bank publisher
+/// - `name`: The bank name
+/// - `version`: The bank version, if set
+/// - `engine`: The `engine.devalang` requirement, if set
+/// - `triggers`: The triggers recorded in bank.toml
+///
+pub fn bank_section(
+    publisher: &str,
+    name: &str,
+    version: Option<&str>,
+    engine: Option<&str>,
+    triggers: &[TriggerDoc],
+) -> String {
+    let mut out = String::from("## Triggers\n\n");
+    if triggers.is_empty() {
+        out.push_str("No audio files found in `audio/` yet.\n");
+    } else {
+        out.push_str("| Trigger | Path | Duration | Channels |\n");
+        out.push_str("|---------|------|----------|----------|\n");
+        for t in triggers {
+            let (duration, channels) = match t.audio {
+                Some(info) => (
+                    format!("{:.2} s", info.duration_secs),
+                    info.channels.to_string(),
+                ),
+                None => ("—".to_string(), "—".to_string()),
+            };
+            out.push_str(&format!(
+                "| `{}` | `{}` | {} | {} |\n",
+                t.name, t.path, duration, channels
+            ));
+        }
+    }
+
+    out.push_str("\n## Use in a Devalang project\n\n");
+    if let Some(req) = engine {
+        out.push_str(&format!("Requires Devalang `{}`.\n\n", req));
+    }
+    out.push_str("Add the bank to your project's `.devalang` file:\n\n```toml\n[[banks]]\n");
+    out.push_str(&format!(
+        "path = \"devalang://bank/{}.{}\"\n",
+        publisher, name
+    ));
+    if let Some(v) = version {
+        out.push_str(&format!("version = \"{}\"\n", v));
+    }
+    out.push_str("```\n");
+    out
+}
+
+/// Generated README content for a plugin: its exports, the parameters its setters
+/// expose, and a Devalang usage example.
+///
+/// ### Parameters
+/// - `publisher`: The plugin publisher
+/// - `name`: The plugin name
+/// - `engine`: The `engine.devalang` requirement, if known
+/// - `exports`: The exports recorded in plugin.toml
+///
+pub fn plugin_section(
+    publisher: &str,
+    name: &str,
+    engine: Option<&str>,
+    exports: &[String],
+) -> String {
+    let params: Vec<(String, &str)> = exports
+        .iter()
+        .filter_map(|e| setter_param(e).map(|p| (p, e.as_str())))
+        .collect();
+    let entries: Vec<&str> = exports
+        .iter()
+        .filter(|e| setter_param(e).is_none())
+        .map(|e| e.as_str())
+        .collect();
+
+    let mut out = String::from("## Exports\n\n");
+    if let Some(req) = engine {
+        out.push_str(&format!("Requires Devalang `{}`.\n\n", req));
+    }
+    if entries.is_empty() {
+        out.push_str("No exports detected in the last build.\n");
+    } else {
+        for e in &entries {
+            out.push_str(&format!("- `{}`\n", e));
+        }
+    }
+
+    if !params.is_empty() {
+        out.push_str("\n## Parameters\n\n| Parameter | Setter |\n|-----------|--------|\n");
+        for (param, setter) in &params {
+            out.push_str(&format!("| `{}` | `{}` |\n", param, setter));
+        }
+    }
+
+    if let Some(entry) = entries.first() {
+        let alias = name.replace(['-', '.'], "_");
+        out.push_str("\n## Usage\n\n```deva\n");
+        out.push_str(&format!("@use {}.{} as {}\n\n", publisher, name, alias));
+        out.push_str(&format!("let sound = {}.{}", alias, entry));
+        for (param, _) in &params {
+            out.push_str(&format!("\n    -> {}(<value>)", param));
+        }
+        out.push_str("\n```\n");
+    }
+    out
+}
+
+/// Parameter set by a setter export: `set_delay_ms` and `setDelayMs` both give `delay_ms`.
+fn setter_param(export: &str) -> Option<String> {
+    if let Some(rest) = export.strip_prefix("set_") {
+        return (!rest.is_empty()).then(|| rest.to_string());
+    }
+    let rest = export.strip_prefix("set")?;
+    if !rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let mut param = String::new();
+    for (i, c) in rest.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                param.push('_');
+            }
+            param.push(c.to_ascii_lowercase());
+        } else {
+            param.push(c);
+        }
+    }
+    Some(param)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block() -> String {
+        generated_block("## Triggers")
+    }
+
+    #[test]
+    fn appends_a_block_when_there_is_none() {
+        let out = replace_block("# Bank\n\nHand-written.\n", &block()).unwrap();
+        assert_eq!(out, format!("# Bank\n\nHand-written.\n\n{}", block()));
+    }
+
+    #[test]
+    fn replaces_only_the_block() {
+        let readme = format!(
+            "# Bank\n\nBefore.\n\n{}\nold content\n{}\n\nAfter.\n",
+            BEGIN_MARKER, END_MARKER
+        );
+        let out = replace_block(&readme, &block()).unwrap();
+        assert_eq!(out, format!("# Bank\n\nBefore.\n\n{}\nAfter.\n", block()));
+        // Refreshing again changes nothing
+        assert_eq!(replace_block(&out, &block()).unwrap(), out);
+    }
+
+    #[test]
+    fn refuses_an_orphan_marker() {
+        let orphan_begin = format!("# Bank\n\n{}\nHand-written.\n", BEGIN_MARKER);
+        assert!(replace_block(&orphan_begin, &block()).is_err());
+        let orphan_end = format!("# Bank\n\nHand-written.\n{}\n", END_MARKER);
+        assert!(replace_block(&orphan_end, &block()).is_err());
+        // A begin marker left over before a complete block
+        let extra_begin = format!("{}\nMine.\n{}", BEGIN_MARKER, block());
+        assert!(replace_block(&extra_begin, &block()).is_err());
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let readme = format!(
+            "# Bank\r\n\r\n{}\r\nold\r\n{}\r\nAfter.\r\n",
+            BEGIN_MARKER, END_MARKER
+        );
+        let out = replace_block(&readme, &block()).unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "# Bank\r\n\r\n{}\r\n## Triggers\r\n{}\r\nAfter.\r\n",
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    minisign,
+    signing::{self, DetachedSignature},
+    spinner,
+};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether a build signs its archive and whether it insists on a valid signature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureOptions {
+    /// Write `<archive>.sig` with the local key, as `--sign` or `[build] sign = true` do
+    pub sign: bool,
+    /// Fail unless `<archive>.sig` exists and matches the archive
+    pub require: bool,
+    /// Also write a minisign signature, `<archive>.minisig`, when signing
+    pub minisign: bool,
+}
+
+impl SignatureOptions {
+    /// The part of the build cache options these settings account for.
+    pub fn cache_key(&self) -> String {
+        format!(
+            "sign={} require_signature={} minisign={}",
+            self.sign, self.require, self.minisign
+        )
+    }
+}
+
+/// Makes the signing key ready to use: creates it when missing (outside CI) and asks for
+/// its passphrase, before any spinner starts.
+pub fn prepare_key() -> Result<(), String> {
+    if signing::ensure_keypair()? {
+        Logger::new().log_message(
+            LogLevel::Info,
+            &format!(
+                "Generated a signing key at {}",
+                signing::key_path()?.display()
+            ),
+        );
+    }
+    signing::unlock()
+}
+
+/// Whether an addon manifest asks for signing on every build (`[build] sign = true`).
+///
+/// ### Parameters
+/// - `manifest`: The bank.toml or plugin.toml path
+///
+pub fn manifest_requests_signing(manifest: &Path) -> bool {
+    fs::read_to_string(manifest)
+        .ok()
+        .and_then(|txt| toml::from_str::<toml::Value>(&txt).ok())
+        .and_then(|doc| doc.get("build")?.get("sign")?.as_bool())
+        .unwrap_or(false)
+}
+
+/// Signs an archive and verifies the signature when required, returning the signature
+/// files to record among the build outputs.
+///
+/// ### Parameters
+/// - `archive`: The archive the build just produced
+/// - `options`: The signing options
+/// - `manifest_sign`: Whether the addon manifest asks for signing (`[build] sign = true`)
+///
+pub fn sign_and_check(
+    archive: &Path,
+    options: SignatureOptions,
+    manifest_sign: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let sig_path = signing::signature_path(archive);
+    let mut outputs = Vec::new();
+    if options.sign || manifest_sign {
+        prepare_key()?;
+        spinner::run_step(
+            &format!("Signing {}", file_name(archive)),
+            |sig: &DetachedSignature| {
+                format!(
+                    "Signed with key {}",
+                    sig.key_id.as_deref().unwrap_or("unknown")
+                )
+            },
+            || signing::sign_file(archive),
+        )?;
+        outputs.push(sig_path.clone());
+        if options.minisign {
+            spinner::run_step(
+                &format!("Writing minisign signature for {}", file_name(archive)),
+                |key_id: &String| format!("Signed with minisign key {}", key_id),
+                || minisign::sign_file(archive),
+            )?;
+            outputs.push(minisign::signature_path(archive));
+        }
+    }
+
+    if options.require {
+        spinner::run_unit_step(
+            &format!("Checking signature at {}", sig_path.display()),
+            "Signature matches the archive",
+            || check_signature(archive, &sig_path),
+        )?;
+    }
+
+    if options.require && outputs.is_empty() {
+        outputs.push(sig_path);
+    }
+    Ok(outputs)
+}
+
+/// Checks that a detached signature exists and covers the current archive.
+///
+/// Only the local keys (current and retired) are accepted: the public key written in the
+/// signature file says nothing about who may sign, so it is ignored.
+fn check_signature(archive: &Path, sig_path: &Path) -> Result<(), String> {
+    if !sig_path.exists() {
+        return Err(format!(
+            "Signing required but signature file not found at {}; build with --sign",
+            sig_path.display()
+        ));
+    }
+    let sig = signing::read_signature(sig_path)?;
+    let keys = signing::local_public_keys();
+    if keys.is_empty() {
+        return Err(format!(
+            "Signing required but there is no local key to check {} with; run 'devapack key generate'",
+            sig_path.display()
+        ));
+    }
+    let bytes =
+        fs::read(archive).map_err(|e| format!("Failed to read {}: {}", archive.display(), e))?;
+    let digest = Sha256::digest(&bytes);
+    let mut matched = false;
+    for public in &keys {
+        matched |= signing::verify_bytes(public, &digest, &sig.signature)?;
+    }
+    let foreign = sig
+        .public_key
+        .as_deref()
+        .and_then(|b64| signing::decode_public_key(b64).ok())
+        .filter(|public| !keys.contains(public));
+    if matched {
+        Ok(())
+    } else if let Some(public) = foreign {
+        Err(format!(
+            "Signature at {} was made with key {}, which is not one of your signing keys",
+            sig_path.display(),
+            signing::fingerprint(&public)
+        ))
+    } else {
+        Err(format!(
+            "Signature at {} does not match {}; it is stale or was made for another file, rebuild with --sign",
+            sig_path.display(),
+            archive.display()
+        ))
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("archive")
+}
@@ -0,0 +1,327 @@
+use crate::utils::{
+    api::get_forge_api_base_url,
+    auth::load_session_token,
+    key_source::{self, KeySource},
+    keyfile,
+    signing::{self, key_path},
+};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Outcome of a single environment check.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl CheckResult {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        CheckResult {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        CheckResult {
+            name: name.to_string(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        CheckResult {
+            name: name.to_string(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Checks that a Rust toolchain binary is on PATH and reports its version.
+///
+/// ### Parameters
+/// - `bin`: The binary name (`cargo` or `rustc`)
+///
+pub fn check_tool(bin: &str) -> CheckResult {
+    let name = format!("{} on PATH", bin);
+    let path = match which::which(bin) {
+        Ok(p) => p,
+        Err(_) => {
+            return CheckResult::fail(
+                &name,
+                format!("'{}' was not found on PATH", bin),
+                "Install Rust from https://rustup.rs and restart your shell",
+            );
+        }
+    };
+    match command_stdout(Command::new(&path).arg("--version")) {
+        Some(version) => CheckResult::ok(&name, format!("{} ({})", version, path.display())),
+        None => CheckResult::fail(
+            &name,
+            format!("'{} --version' failed ({})", bin, path.display()),
+            "Reinstall the toolchain with 'rustup update'",
+        ),
+    }
+}
+
+/// Checks that the standard library for `wasm32-unknown-unknown` is installed.
+pub fn check_wasm_target() -> CheckResult {
+    let name = "wasm32 target";
+    let fix = format!("rustup target add {}", WASM_TARGET);
+
+    // Looking in the sysroot works with and without rustup.
+    let sysroot = command_stdout(Command::new("rustc").args(["--print", "sysroot"]));
+    let Some(sysroot) = sysroot else {
+        return CheckResult::fail(name, "Could not query rustc sysroot", fix);
+    };
+    let target_dir = Path::new(&sysroot)
+        .join("lib")
+        .join("rustlib")
+        .join(WASM_TARGET);
+    if target_dir.is_dir() {
+        CheckResult::ok(name, format!("{} installed", WASM_TARGET))
+    } else {
+        CheckResult::fail(
+            name,
+            format!("{} is not installed for {}", WASM_TARGET, sysroot),
+            fix,
+        )
+    }
+}
+
+/// Checks that `~/.devalang/config.json` holds a session token.
+pub fn check_session() -> CheckResult {
+    let name = "Forge session";
+    match load_session_token() {
+        Ok(token) if !token.trim().is_empty() => {
+            CheckResult::ok(name, "Session token found in ~/.devalang/config.json")
+        }
+        Ok(_) => CheckResult::warn(
+            name,
+            "Session token in ~/.devalang/config.json is empty",
+            "Run 'devalang login' to refresh your session",
+        ),
+        Err(e) => CheckResult::warn(
+            name,
+            e,
+            "Run 'devalang login' before submitting or updating addons",
+        ),
+    }
+}
+
+/// Checks that the signing key exists and has a supported length (32 or 64 bytes).
+pub fn check_signing_key() -> CheckResult {
+    let name = "Signing key";
+    let source = match KeySource::current() {
+        Ok(source) => source,
+        Err(e) => {
+            return CheckResult::fail(
+                name,
+                e,
+                format!(
+                    "Unset {} or {}",
+                    key_source::KEY_ENV,
+                    key_source::KEY_FILE_ENV
+                ),
+            );
+        }
+    };
+    match &source {
+        KeySource::DefaultFile(_) => {}
+        // Reading stdin here would take the key away from the command that needs it
+        KeySource::Stdin => {
+            return CheckResult::ok(name, "read from stdin when signing");
+        }
+        KeySource::Env | KeySource::File(_) => {
+            return match source
+                .read()
+                .and_then(|bytes| signing::public_key_from_file_bytes(&bytes))
+            {
+                Ok(public) => CheckResult::ok(
+                    name,
+                    format!(
+                        "{} (fingerprint {})",
+                        source.describe(),
+                        signing::fingerprint(&public)
+                    ),
+                ),
+                Err(e) => CheckResult::fail(
+                    name,
+                    format!("{}: {}", source.describe(), e),
+                    format!(
+                        "Set {} to a base64 key, or {} to a key file",
+                        key_source::KEY_ENV,
+                        key_source::KEY_FILE_ENV
+                    ),
+                ),
+            };
+        }
+    }
+    let path = match key_path() {
+        Ok(p) => p,
+        Err(e) => return CheckResult::fail(name, e, "Make sure your home directory is set"),
+    };
+    if !path.exists() {
+        // CI never generates a key, so signing would fail
+        if key_source::ci_mode() {
+            return CheckResult::fail(
+                name,
+                format!("No key at {} in CI mode", path.display()),
+                format!(
+                    "Set {} to a base64 key, or {} to a key file",
+                    key_source::KEY_ENV,
+                    key_source::KEY_FILE_ENV
+                ),
+            );
+        }
+        return CheckResult::warn(
+            name,
+            format!("No key at {}", path.display()),
+            "Run 'devapack key generate'",
+        );
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(&path) {
+            let mode = meta.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return CheckResult::warn(
+                    name,
+                    format!(
+                        "{} is readable by other users (mode {:o})",
+                        path.display(),
+                        mode
+                    ),
+                    format!("Run 'chmod 600 {}'", path.display()),
+                );
+            }
+        }
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) if keyfile::is_encrypted(&bytes) => match keyfile::public_key(&bytes) {
+            Ok(_) => CheckResult::ok(name, format!("{} (encrypted)", path.display())),
+            Err(e) => CheckResult::fail(
+                name,
+                format!("{}: {}", path.display(), e),
+                "Restore the key from a backup, or 'devapack key import' a valid key",
+            ),
+        },
+        Ok(bytes) if bytes.len() == 32 || bytes.len() == 64 => CheckResult::warn(
+            name,
+            format!("{} is stored unencrypted", path.display()),
+            "Run 'devapack key passphrase' to encrypt it",
+        ),
+        Ok(bytes) => CheckResult::fail(
+            name,
+            format!(
+                "{} is {} bytes; expected a 32-byte secret or 64-byte keypair",
+                path.display(),
+                bytes.len()
+            ),
+            format!(
+                "Move {} aside and run 'devapack key generate', or 'devapack key import' a valid key",
+                path.display()
+            ),
+        ),
+        Err(e) => CheckResult::fail(
+            name,
+            format!("Failed to read {}: {}", path.display(), e),
+            "Check the file permissions on ~/.devalang/keys",
+        ),
+    }
+}
+
+/// Checks that the Forge API answers on `endpoint`.
+///
+/// ### Parameters
+/// - `endpoint`: Path appended to `DEVALANG_FORGE_API_URL` (e.g. `/`)
+///
+pub async fn check_forge(endpoint: &str) -> CheckResult {
+    let name = "Forge API";
+    let base = get_forge_api_base_url();
+    let url = format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        endpoint.trim_start_matches('/')
+    );
+    let fix = "Check your network or set DEVALANG_FORGE_API_URL to a reachable Forge instance";
+
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => return CheckResult::fail(name, format!("HTTP client error: {}", e), fix),
+    };
+    match client.get(&url).send().await {
+        Ok(resp) if resp.status().is_server_error() => CheckResult::warn(
+            name,
+            format!("{} answered {}", url, resp.status()),
+            "The Forge may be down; try again later",
+        ),
+        Ok(resp) => CheckResult::ok(name, format!("{} answered {}", url, resp.status())),
+        Err(e) => CheckResult::fail(name, format!("{} is unreachable: {}", url, e), fix),
+    }
+}
+
+/// Checks that devapack can write to `<cwd>/<dir>`.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `dir`: The project directory to probe (`generated` or `output`)
+///
+pub fn check_writable(cwd: &str, dir: &str) -> CheckResult {
+    let name = format!("{}/ writable", dir);
+    let target = Path::new(cwd).join(dir);
+    // When the directory does not exist yet, it will be created inside cwd.
+    let probe_dir: PathBuf = if target.is_dir() {
+        target.clone()
+    } else {
+        PathBuf::from(cwd)
+    };
+    let probe = probe_dir.join(".devapack-doctor");
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            let detail = if target.is_dir() {
+                format!("{} is writable", target.display())
+            } else {
+                format!("{} will be created on first use", target.display())
+            };
+            CheckResult::ok(&name, detail)
+        }
+        Err(e) => CheckResult::fail(
+            &name,
+            format!("Cannot write to {}: {}", probe_dir.display(), e),
+            format!("Fix the permissions on {}", probe_dir.display()),
+        ),
+    }
+}
+
+fn command_stdout(cmd: &mut Command) -> Option<String> {
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
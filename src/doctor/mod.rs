@@ -0,0 +1,63 @@
+pub mod checks;
+
+use crate::utils::logger::{LogLevel, Logger};
+use checks::{CheckResult, CheckStatus};
+
+/// Runs every environment check and prints a fix for each problem found.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `endpoint`: Forge API path used for the reachability check
+///
+pub async fn run_doctor(cwd: &str, endpoint: &str) -> Result<(), String> {
+    let results: Vec<CheckResult> = vec![
+        checks::check_tool("cargo"),
+        checks::check_tool("rustc"),
+        checks::check_wasm_target(),
+        checks::check_session(),
+        checks::check_signing_key(),
+        checks::check_forge(endpoint).await,
+        checks::check_writable(cwd, "generated"),
+        checks::check_writable(cwd, "output"),
+    ];
+
+    let logger = Logger::new();
+    for r in &results {
+        let level = match r.status {
+            CheckStatus::Ok => LogLevel::Success,
+            CheckStatus::Warn => LogLevel::Warning,
+            CheckStatus::Fail => LogLevel::Error,
+        };
+        let mut trace = vec![r.detail.clone()];
+        if let Some(fix) = &r.fix {
+            trace.push(format!("Fix: {}", fix));
+        }
+        let refs: Vec<&str> = trace.iter().map(|s| s.as_str()).collect();
+        logger.log_message_with_trace(level, &r.name, refs);
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count();
+    let warned = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Warn)
+        .count();
+
+    if failed > 0 {
+        return Err(format!(
+            "{} check(s) failed, {} warning(s); see the fixes above",
+            failed, warned
+        ));
+    }
+    if warned > 0 {
+        logger.log_message(
+            LogLevel::Info,
+            &format!("All required checks passed ({} warning(s))", warned),
+        );
+    } else {
+        logger.log_message(LogLevel::Success, "All checks passed");
+    }
+    Ok(())
+}
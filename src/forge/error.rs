@@ -0,0 +1,155 @@
+use crate::forge::models::{ErrorBody, FieldError};
+use reqwest::StatusCode;
+use std::fmt;
+
+/// Why a call to the Forge API failed.
+#[derive(Debug, Clone)]
+pub enum ForgeError {
+    /// No session token was found: the user has not logged in
+    NotLoggedIn(String),
+    /// The request could not be built
+    InvalidRequest(String),
+    /// The request could not be sent, or its response could not be read
+    Network(String),
+    /// Forge answered with an error status
+    Http {
+        status: StatusCode,
+        /// The server's `message`, or a short response body that is not JSON
+        message: String,
+        /// Per-field errors listed under `payload.errors`
+        errors: Vec<FieldError>,
+    },
+    /// Forge answered successfully, but not with the expected data
+    InvalidResponse(String),
+}
+
+impl ForgeError {
+    /// Builds the error of a non-success response from its status and body.
+    ///
+    /// ### Parameters
+    /// - `status`: The HTTP status
+    /// - `body`: The response body
+    ///
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        let (message, errors) = match serde_json::from_str::<ErrorBody>(body) {
+            Ok(parsed) => (
+                parsed.message.or(parsed.error).unwrap_or_default(),
+                parsed.payload.map(|p| p.errors).unwrap_or_default(),
+            ),
+            // A proxy or server error page says no more than the status does
+            Err(_) if body.trim().lines().count() > 1 || body.trim().len() > 200 => {
+                (String::new(), Vec::new())
+            }
+            Err(_) => (body.trim().to_string(), Vec::new()),
+        };
+        Self::Http {
+            status,
+            message,
+            errors,
+        }
+    }
+
+    /// The HTTP status Forge answered with, if it answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ForgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotLoggedIn(msg)
+            | Self::InvalidRequest(msg)
+            | Self::Network(msg)
+            | Self::InvalidResponse(msg) => {
+                write!(f, "{}", msg)
+            }
+            Self::Http {
+                status,
+                message,
+                errors,
+            } => {
+                write!(f, "HTTP {}", status)?;
+                if !message.is_empty() {
+                    write!(f, ": {}", message)?;
+                }
+                for err in errors {
+                    write!(f, "\n-> {} : {}", err.code, err.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ForgeError {}
+
+impl From<ForgeError> for String {
+    fn from(e: ForgeError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The message and `(code, message)` pairs of an `Http` error.
+    fn parts(err: &ForgeError) -> (&str, Vec<(&str, &str)>) {
+        match err {
+            ForgeError::Http {
+                message, errors, ..
+            } => (
+                message.as_str(),
+                errors
+                    .iter()
+                    .map(|e| (e.code.as_str(), e.message.as_str()))
+                    .collect(),
+            ),
+            other => panic!("expected an Http error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_json_message_and_field_errors() {
+        let body = r#"{"message":"Validation failed","payload":{"errors":[{"code":"name","message":"already taken"},{"message":"no code"}]}}"#;
+        let err = ForgeError::from_response(StatusCode::UNPROCESSABLE_ENTITY, body);
+        assert_eq!(err.status(), Some(StatusCode::UNPROCESSABLE_ENTITY));
+        let (message, errors) = parts(&err);
+        assert_eq!(message, "Validation failed");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0], ("name", "already taken"));
+        assert_eq!(errors[1].1, "no code");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "HTTP 422 Unprocessable Entity: Validation failed\n-> name : already taken\n-> {} : no code",
+                errors[1].0
+            )
+        );
+    }
+
+    #[test]
+    fn falls_back_to_json_error_field() {
+        let err = ForgeError::from_response(StatusCode::UNAUTHORIZED, r#"{"error":"bad token"}"#);
+        assert_eq!(parts(&err), ("bad token", vec![]));
+    }
+
+    #[test]
+    fn keeps_a_short_plain_body_as_message() {
+        let err = ForgeError::from_response(StatusCode::BAD_GATEWAY, "  upstream timed out\n");
+        assert_eq!(parts(&err), ("upstream timed out", vec![]));
+        assert_eq!(err.to_string(), "HTTP 502 Bad Gateway: upstream timed out");
+    }
+
+    #[test]
+    fn drops_an_html_error_page() {
+        let body = "<html>\n<head><title>500 Internal Server Error</title></head>\n<body></body>\n</html>\n";
+        let err = ForgeError::from_response(StatusCode::INTERNAL_SERVER_ERROR, body);
+        assert_eq!(parts(&err), ("", vec![]));
+        assert_eq!(err.to_string(), "HTTP 500 Internal Server Error");
+    }
+}
@@ -0,0 +1,261 @@
+pub mod error;
+pub mod models;
+
+pub use error::ForgeError;
+
+use crate::types::publisher::{PublisherInfo, PublisherInfoUpdate};
+use crate::utils::{api::get_forge_api_base_url, auth, version::get_version};
+use models::{
+    AddonRecord, AddonSubmitted, AddonUpload, KeyRegistration, PublisherList, SignRequest,
+};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Client of the Forge API.
+///
+/// Every request goes to `DEVALANG_FORGE_API_URL` (Forge itself by default) and, once
+/// logged in, carries the session token as a bearer token.
+pub struct ForgeClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ForgeClient {
+    /// A client for the public endpoints, which need no session.
+    pub fn anonymous() -> Result<Self, ForgeError> {
+        let http = reqwest::Client::builder()
+            .user_agent(format!("devapack/{}", get_version()))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| {
+                ForgeError::InvalidRequest(format!("Failed to build HTTP client: {}", e))
+            })?;
+        Ok(Self {
+            http,
+            base_url: get_forge_api_base_url().trim_end_matches('/').to_string(),
+            token: None,
+        })
+    }
+
+    /// A client acting as the logged-in user, with the session of `~/.devalang/config.json`.
+    pub fn authenticated() -> Result<Self, ForgeError> {
+        let token = auth::load_session_token().map_err(ForgeError::NotLoggedIn)?;
+        Ok(Self {
+            token: Some(token),
+            ..Self::anonymous()?
+        })
+    }
+
+    /// Looks an addon up by publisher and name.
+    ///
+    /// ### Parameters
+    /// - `publisher`: The publisher identifier
+    /// - `name`: The addon name
+    ///
+    pub async fn get_addon(&self, publisher: &str, name: &str) -> Result<AddonRecord, ForgeError> {
+        let path = format!("/v1/addon/get/{}/{}", publisher, name);
+        self.send(self.request(Method::GET, &path)).await
+    }
+
+    /// Submits a new addon.
+    ///
+    /// ### Parameters
+    /// - `upload`: The addon metadata, sources and built archive
+    ///
+    pub async fn submit_addon(
+        &self,
+        upload: AddonUpload<'_>,
+    ) -> Result<AddonSubmitted, ForgeError> {
+        let form = self.addon_form(upload)?;
+        self.send(
+            self.request(Method::POST, "/v1/addon/submit")
+                .multipart(form),
+        )
+        .await
+    }
+
+    /// Uploads a new version of an existing addon.
+    ///
+    /// ### Parameters
+    /// - `addon_id`: The Forge id of the addon
+    /// - `upload`: The addon metadata, sources and built archive
+    ///
+    pub async fn update_addon(
+        &self,
+        addon_id: &str,
+        upload: AddonUpload<'_>,
+    ) -> Result<AddonSubmitted, ForgeError> {
+        let form = self.addon_form(upload)?;
+        let path = format!("/v1/addon/update/{}", addon_id);
+        self.send(self.request(Method::POST, &path).multipart(form))
+            .await
+    }
+
+    /// Makes the last uploaded version of an addon public.
+    ///
+    /// ### Parameters
+    /// - `addon_id`: The Forge id of the addon
+    ///
+    pub async fn publish_addon(&self, addon_id: &str) -> Result<(), ForgeError> {
+        let path = format!("/v1/addon/publish/{}", addon_id);
+        self.send_empty(self.request(Method::POST, &path)).await
+    }
+
+    /// Registers the signature envelope of an uploaded archive.
+    ///
+    /// Returns the payload of the answer as is; its fields vary between Forge versions
+    /// and are only displayed.
+    ///
+    /// ### Parameters
+    /// - `addon_id`: The Forge id of the addon
+    /// - `request`: The envelope and the archive it covers
+    ///
+    pub async fn sign_addon(
+        &self,
+        addon_id: &str,
+        request: &SignRequest<'_>,
+    ) -> Result<serde_json::Value, ForgeError> {
+        let path = format!("/v1/addon/sign/{}", addon_id);
+        self.send(self.request(Method::POST, &path).json(request))
+            .await
+    }
+
+    /// Registers a public signing key with the account.
+    ///
+    /// ### Parameters
+    /// - `key`: The public key and its fingerprint
+    ///
+    pub async fn register_key(&self, key: &KeyRegistration<'_>) -> Result<(), ForgeError> {
+        self.send_empty(self.request(Method::POST, "/v1/key/register").json(key))
+            .await
+    }
+
+    /// Lists the publishers of the account.
+    pub async fn list_publishers(&self) -> Result<Vec<PublisherInfo>, ForgeError> {
+        let list: PublisherList = self
+            .send(self.request(Method::GET, "/v1/publisher/list"))
+            .await?;
+        Ok(list.publishers)
+    }
+
+    /// Creates a publisher owned by the account.
+    ///
+    /// ### Parameters
+    /// - `publisher`: The publisher to create
+    ///
+    pub async fn create_publisher(&self, publisher: &PublisherInfo) -> Result<(), ForgeError> {
+        self.send_empty(
+            self.request(Method::POST, "/v1/publisher/create")
+                .json(publisher),
+        )
+        .await
+    }
+
+    /// Updates the profile of a publisher.
+    ///
+    /// ### Parameters
+    /// - `identifier`: The publisher identifier
+    /// - `publisher`: The new profile
+    ///
+    pub async fn update_publisher(
+        &self,
+        identifier: &str,
+        publisher: &PublisherInfoUpdate,
+    ) -> Result<(), ForgeError> {
+        let path = format!("/v1/publisher/update/{}", identifier);
+        self.send_empty(self.request(Method::POST, &path).json(publisher))
+            .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let builder = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    /// Sends a request and decodes its `payload` (or the whole body when there is none).
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ForgeError> {
+        let body = self.send_raw(request).await?;
+        let json: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
+            ForgeError::InvalidResponse(format!("Failed to parse response JSON: {}", e))
+        })?;
+        let payload = match json.get("payload") {
+            Some(payload) => payload.clone(),
+            None => json,
+        };
+        serde_json::from_value(payload)
+            .map_err(|e| ForgeError::InvalidResponse(format!("Unexpected response: {}", e)))
+    }
+
+    /// Sends a request whose answer carries nothing but its status.
+    async fn send_empty(&self, request: RequestBuilder) -> Result<(), ForgeError> {
+        self.send_raw(request).await.map(|_| ())
+    }
+
+    async fn send_raw(&self, request: RequestBuilder) -> Result<String, ForgeError> {
+        let response = request.send().await.map_err(|e| {
+            ForgeError::Network(format!("Failed to send request to Forge API: {}", e))
+        })?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ForgeError::Network(format!("Failed to read response body: {}", e)))?;
+        if !status.is_success() {
+            return Err(ForgeError::from_response(status, &body));
+        }
+        Ok(body)
+    }
+
+    fn addon_form(&self, upload: AddonUpload<'_>) -> Result<Form, ForgeError> {
+        let addon = upload.addon;
+        let mut form = Form::new()
+            .text("name", addon.name.clone())
+            .text("type", addon.addon_type.clone())
+            .text("publisher", addon.publisher.clone())
+            .text("version", addon.version.clone())
+            .text("access", addon.access.clone());
+        // Older Forge versions read the session from the form rather than the header
+        if let Some(token) = &self.token {
+            form = form.text("user_session", token.clone());
+        }
+        if let Some(engine) = &addon.engine {
+            form = form.text("engine_devalang", engine.clone());
+        }
+        if let Some(source) = upload.source {
+            form = form.part("files", Part::bytes(source).file_name("source.tar.gz"));
+        }
+        if let Some(archive) = upload.archive {
+            form = form.part(
+                "files",
+                Part::bytes(archive.gzip).file_name("archive.tar.gz"),
+            );
+            if let Some(envelope) = &archive.envelope {
+                let invalid = ForgeError::InvalidRequest;
+                form = form
+                    .text("signature_envelope", envelope.to_json().map_err(invalid)?)
+                    .text(
+                        "public_key",
+                        envelope.statement().map_err(invalid)?.public_key,
+                    );
+            }
+            if let Some(signature) = archive.signature {
+                form = form.text("signature", signature);
+            }
+            if let Some(signature_gzip) = archive.signature_gzip {
+                form = form.text("signature_gzip", signature_gzip);
+            }
+            form = form
+                .text("archive_sha256", archive.sha256)
+                .text("archive_gzip_sha256", archive.gzip_sha256);
+        }
+        Ok(form)
+    }
+}
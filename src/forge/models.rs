@@ -0,0 +1,110 @@
+use crate::addon::self_sign::SignatureEnvelope;
+use crate::types::{addon::AddonSubmissionData, publisher::PublisherInfo};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// An addon as sent to `/v1/addon/submit` and `/v1/addon/update/<id>`.
+pub struct AddonUpload<'a> {
+    pub addon: &'a AddonSubmissionData,
+    /// Gzipped tar of the addon sources
+    pub source: Option<Vec<u8>>,
+    /// The built archive, when there is one in `output/`
+    pub archive: Option<ArchiveUpload>,
+}
+
+/// A built addon archive and what vouches for it.
+pub struct ArchiveUpload {
+    /// The gzipped archive
+    pub gzip: Vec<u8>,
+    /// Hex SHA-256 of the uncompressed tar
+    pub sha256: String,
+    /// Hex SHA-256 of `gzip`
+    pub gzip_sha256: String,
+    /// The signature of the archive, when a signing key is available
+    pub envelope: Option<SignatureEnvelope>,
+    /// Base64 signature of the raw SHA-256 digest of the tar, for Forge servers that
+    /// predate the envelope; only sent with `--legacy-signatures`, as it can be replayed
+    pub signature: Option<String>,
+    /// Base64 signature of the raw SHA-256 digest of `gzip`, for the same servers
+    pub signature_gzip: Option<String>,
+}
+
+/// Answer to a submission or an update.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddonSubmitted {
+    #[serde(deserialize_with = "forge_id")]
+    pub addon_id: String,
+}
+
+/// An addon as returned by `/v1/addon/get/<publisher>/<name>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddonRecord {
+    #[serde(alias = "addon_id", alias = "addonId", deserialize_with = "forge_id")]
+    pub id: String,
+}
+
+/// Body of `/v1/addon/sign/<id>`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignRequest<'a> {
+    /// Base64 public key of the signer
+    pub public_key: String,
+    pub signature_envelope: &'a SignatureEnvelope,
+    /// Hex SHA-256 of the uncompressed archive
+    pub archive_sha256: &'a str,
+}
+
+/// Body of `/v1/key/register`.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyRegistration<'a> {
+    pub algorithm: &'a str,
+    /// Base64 Ed25519 public key
+    pub public_key: &'a str,
+    /// Fingerprint of `public_key`
+    pub key_id: &'a str,
+}
+
+/// Answer of `/v1/publisher/list`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublisherList {
+    pub publishers: Vec<PublisherInfo>,
+}
+
+/// Body of an error response.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ErrorBody {
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub payload: Option<ErrorPayload>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ErrorPayload {
+    #[serde(default)]
+    pub errors: Vec<FieldError>,
+}
+
+/// One validation error of a rejected request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldError {
+    #[serde(default = "unknown_code")]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+fn unknown_code() -> String {
+    "UNKNOWN".to_string()
+}
+
+/// Reads a Forge id, which the API sends either as a string or as a number.
+fn forge_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Text(String),
+        Number(serde_json::Number),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::Text(s) => s,
+        Id::Number(n) => n.to_string(),
+    })
+}
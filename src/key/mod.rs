@@ -0,0 +1,323 @@
+use crate::forge::{ForgeClient, models::KeyRegistration};
+use crate::utils::{
+    key_source::KeySource,
+    keyfile,
+    logger::{LogLevel, Logger},
+    minisign, signing,
+};
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::Keypair;
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Creates the local signing key, refusing to overwrite an existing one.
+///
+/// ### Parameters
+/// - `no_passphrase`: Store the key unencrypted
+///
+pub fn generate_key(no_passphrase: bool) -> Result<(), String> {
+    let path = signing::key_path()?;
+    if path.exists() {
+        return Err(format!(
+            "A key already exists at {}; use 'devapack key rotate' to replace it",
+            path.display()
+        ));
+    }
+    let passphrase = passphrase_for_new_key(no_passphrase)?;
+    let kp = signing::generate_keypair()?;
+    signing::write_key(&path, &kp, passphrase.as_deref().map(|p| p.as_str()))?;
+    log_key(LogLevel::Success, "Signing key generated", &path, &kp);
+    Ok(())
+}
+
+/// Prints where the signing key comes from, its format, public key and fingerprint, and
+/// the retired keys.
+pub fn show_key() -> Result<(), String> {
+    let source = KeySource::current()?;
+    if !source.exists() {
+        return Err(format!(
+            "No key at {}; run 'devapack key generate'",
+            source.describe()
+        ));
+    }
+    let bytes = source.read()?;
+    let public = signing::public_key_from_file_bytes(&bytes)?;
+    let format = if keyfile::is_encrypted(&bytes) {
+        format!(
+            "encrypted (Argon2id, XChaCha20-Poly1305, format v{})",
+            keyfile::FORMAT_VERSION
+        )
+    } else if bytes.len() == 64 {
+        "64-byte keypair, unencrypted".to_string()
+    } else {
+        "32-byte secret, unencrypted".to_string()
+    };
+
+    let mut trace = vec![
+        format!("Source: {}", source.describe()),
+        format!("Format: {}", format),
+        format!(
+            "Public key: {}",
+            general_purpose::STANDARD.encode(public.to_bytes())
+        ),
+        format!("Fingerprint: {}", signing::fingerprint(&public)),
+        format!(
+            "Minisign key id: {}",
+            minisign::key_id_hex(&minisign::key_id(&public))
+        ),
+    ];
+    // The current key comes first; the rest are retired
+    for public in signing::local_public_keys().iter().skip(1) {
+        trace.push(format!(
+            "Retired: {} (still used to verify)",
+            signing::fingerprint(public)
+        ));
+    }
+    let refs: Vec<&str> = trace.iter().map(|s| s.as_str()).collect();
+    Logger::new().log_message_with_trace(LogLevel::Info, "Signing key", refs);
+    Ok(())
+}
+
+/// Prints the base64 public key, or writes it to a file.
+///
+/// ### Parameters
+/// - `output`: The file to write, instead of printing to stdout
+/// - `minisign`: Export a minisign public key file instead
+///
+pub fn export_public_key(output: Option<&str>, minisign: bool) -> Result<(), String> {
+    let public = signing::local_public_key()?;
+    let text = if minisign {
+        minisign::encode_public_key(&public)
+    } else {
+        format!("{}\n", general_purpose::STANDARD.encode(public.to_bytes()))
+    };
+    match output {
+        Some(out) => {
+            fs::write(out, text).map_err(|e| format!("Failed to write {}: {}", out, e))?;
+            Logger::new().log_message(
+                LogLevel::Success,
+                &format!(
+                    "Public key {} written to {}",
+                    signing::fingerprint(&public),
+                    out
+                ),
+            );
+        }
+        // Bare output so it can be piped or captured
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Installs a key from a file: an encrypted devapack key file, kept as is, or a 32-byte
+/// secret or 64-byte keypair, raw or base64, which gets encrypted.
+///
+/// An existing key is only replaced with `force`, and is then retired rather than deleted.
+///
+/// ### Parameters
+/// - `file`: The key to import
+/// - `force`: Replace an existing key
+/// - `no_passphrase`: Store a raw key unencrypted
+///
+pub fn import_key(file: &str, force: bool, no_passphrase: bool) -> Result<(), String> {
+    let raw =
+        Zeroizing::new(fs::read(file).map_err(|e| format!("Failed to read {}: {}", file, e))?);
+    if keyfile::is_encrypted(&raw) {
+        let public = signing::public_key_from_file_bytes(&raw)?;
+        let path = replaceable_key_path(force)?;
+        replace_key(&path, |target| signing::write_private_file(target, &raw))?;
+        let path_line = format!("Path: {}", path.display());
+        let fp_line = format!("Fingerprint: {}", signing::fingerprint(&public));
+        Logger::new().log_message_with_trace(
+            LogLevel::Success,
+            "Encrypted signing key imported",
+            vec![&path_line, &fp_line],
+        );
+        return Ok(());
+    }
+    let bytes = Zeroizing::new(match raw.len() {
+        32 | 64 => raw.to_vec(),
+        _ => general_purpose::STANDARD
+            .decode(String::from_utf8_lossy(&raw).trim())
+            .map_err(|_| {
+                format!(
+                    "{} is neither a raw 32/64-byte key nor a base64-encoded one",
+                    file
+                )
+            })?,
+    });
+    let kp = signing::keypair_from_bytes(&bytes)?;
+    let passphrase = passphrase_for_new_key(no_passphrase)?;
+
+    let path = replaceable_key_path(force)?;
+    replace_key(&path, |target| {
+        signing::write_key(target, &kp, passphrase.as_deref().map(|p| p.as_str()))
+    })?;
+    log_key(LogLevel::Success, "Signing key imported", &path, &kp);
+    Ok(())
+}
+
+/// Replaces the local key with a new one, keeping the old key to verify older signatures.
+///
+/// ### Parameters
+/// - `no_passphrase`: Store the new key unencrypted
+///
+pub fn rotate_key(no_passphrase: bool) -> Result<(), String> {
+    let path = signing::key_path()?;
+    if !path.exists() {
+        return Err(format!(
+            "No key at {}; run 'devapack key generate'",
+            path.display()
+        ));
+    }
+    let passphrase = passphrase_for_new_key(no_passphrase)?;
+    let kp = signing::generate_keypair()?;
+    let retired = replace_key(&path, |target| {
+        signing::write_key(target, &kp, passphrase.as_deref().map(|p| p.as_str()))
+    })?;
+    log_key(LogLevel::Success, "Signing key rotated", &path, &kp);
+    if let Some(retired) = retired {
+        Logger::new().log_message(
+            LogLevel::Info,
+            &format!(
+                "Previous key kept at {}; run 'devapack key register' so Forge accepts the new one",
+                retired
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Sets, changes or removes the passphrase of the local key.
+///
+/// Encrypts a key stored in the raw format, which migrates keys made by older versions.
+///
+/// ### Parameters
+/// - `remove`: Store the key unencrypted instead
+///
+pub fn change_passphrase(remove: bool) -> Result<(), String> {
+    let path = signing::key_path()?;
+    if !path.exists() {
+        return Err(format!(
+            "No key at {}; run 'devapack key generate'",
+            path.display()
+        ));
+    }
+    let bytes = Zeroizing::new(
+        fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+    );
+    let kp = signing::keypair_from_file_bytes(&bytes)?;
+    let passphrase = passphrase_for_new_key(remove)?;
+    signing::write_key(&path, &kp, passphrase.as_deref().map(|p| p.as_str()))?;
+    let message = if remove {
+        "Signing key passphrase removed; the key is stored unencrypted"
+    } else {
+        "Signing key encrypted with the new passphrase"
+    };
+    log_key(LogLevel::Success, message, &path, &kp);
+    Ok(())
+}
+
+/// Uploads the public key to Forge so signatures made with it are accepted.
+pub async fn register_key() -> Result<(), String> {
+    let public = signing::local_public_key()?;
+    let public_b64 = general_purpose::STANDARD.encode(public.to_bytes());
+    let key_id = signing::fingerprint(&public);
+    ForgeClient::authenticated()?
+        .register_key(&KeyRegistration {
+            algorithm: "ed25519",
+            public_key: &public_b64,
+            key_id: &key_id,
+        })
+        .await
+        .map_err(|e| format!("Failed to register key: {}", e))?;
+    Logger::new().log_message(
+        LogLevel::Success,
+        &format!("Public key {} registered with Forge", key_id),
+    );
+    Ok(())
+}
+
+/// Moves the current key to the retired keys directory, named after its fingerprint.
+fn retire_current_key(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let public = signing::public_key_from_file_bytes(&bytes)?;
+    let dir = signing::retired_keys_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let target = dir.join(format!("ed25519-{}.key", signing::fingerprint(&public)));
+    fs::rename(path, &target).map_err(|e| {
+        format!(
+            "Failed to move {} to {}: {}",
+            path.display(),
+            target.display(),
+            e
+        )
+    })?;
+    Ok(target.display().to_string())
+}
+
+/// Installs a key at `path`, retiring the current one only once the new key is on disk,
+/// so a failed write leaves the current key in place.
+///
+/// Returns where the previous key was retired to, if there was one.
+///
+/// ### Parameters
+/// - `path`: The key path
+/// - `write`: Writes the new key to the path it is given
+///
+fn replace_key(
+    path: &Path,
+    write: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<Option<String>, String> {
+    if !path.exists() {
+        write(path)?;
+        return Ok(None);
+    }
+    let staged = path.with_extension("key.new");
+    let retired = write(&staged).and_then(|_| retire_current_key(path));
+    let retired = match retired {
+        Ok(retired) => retired,
+        Err(e) => {
+            let _ = fs::remove_file(&staged);
+            return Err(e);
+        }
+    };
+    fs::rename(&staged, path).map_err(|e| {
+        format!(
+            "Failed to move the new key {} into {}: {} (previous key retired to {})",
+            staged.display(),
+            path.display(),
+            e,
+            retired
+        )
+    })?;
+    Ok(Some(retired))
+}
+
+/// The key path, when there is no key there yet or `force` allows replacing it.
+fn replaceable_key_path(force: bool) -> Result<std::path::PathBuf, String> {
+    let path = signing::key_path()?;
+    if path.exists() && !force {
+        return Err(format!(
+            "A key already exists at {}; pass --force to replace it (it will be retired, not deleted)",
+            path.display()
+        ));
+    }
+    Ok(path)
+}
+
+/// The passphrase to encrypt a new key with, unless the user opted out.
+fn passphrase_for_new_key(no_passphrase: bool) -> Result<Option<Zeroizing<String>>, String> {
+    if no_passphrase {
+        Ok(None)
+    } else {
+        signing::new_passphrase().map(Some)
+    }
+}
+
+fn log_key(level: LogLevel, message: &str, path: &Path, kp: &Keypair) {
+    let path_line = format!("Path: {}", path.display());
+    let fp_line = format!("Fingerprint: {}", signing::fingerprint(&kp.public));
+    Logger::new().log_message_with_trace(level, message, vec![&path_line, &fp_line]);
+}
@@ -1,5 +1,5 @@
 use crate::{
-    builder::{bank as bank_builder, plugin as plugin_builder},
+    builder::{bank as bank_builder, plugin as plugin_builder, sign::SignatureOptions},
     utils::{signature::get_signature, version::get_version},
 };
 use clap::CommandFactory;
@@ -10,9 +10,14 @@ use tokio::io;
 
 mod addon;
 mod builder;
+mod doctor;
+mod forge;
+mod key;
 mod publisher;
+mod trust;
 mod types;
 mod utils;
+mod verify;
 
 #[derive(Parser)]
 #[command(name = "devapack")]
@@ -38,27 +43,103 @@ enum Commands {
     },
 
     /// Submit an addon to the official Devalang repository
-    Submit {},
+    Submit {
+        #[arg(long, default_value_t = false)]
+        /// Also send bare signatures of the archive digests, for Forge servers that predate signature envelopes (they are not bound to the addon or version, so they can be replayed)
+        legacy_signatures: bool,
+    },
 
     /// Update an existing addon in the official Devalang repository
-    Update {},
+    Update {
+        #[arg(long, default_value_t = false)]
+        /// Also send bare signatures of the archive digests, for Forge servers that predate signature envelopes (they are not bound to the addon or version, so they can be replayed)
+        legacy_signatures: bool,
+    },
 
     /// Manage Publishers
     Publisher {
         #[command(subcommand)]
         command: PublisherCommands,
     },
+
+    /// Manage the signing key
+    Key {
+        #[command(subcommand)]
+        command: KeyCommands,
+    },
+
+    /// Manage the publisher keys trusted when verifying addons
+    Trust {
+        #[command(subcommand)]
+        command: TrustCommands,
+    },
+
+    /// Check the local environment and print fixes for common problems
+    Doctor {
+        #[arg(long, default_value = "/")]
+        /// Forge API path used for the reachability check (relative to DEVALANG_FORGE_API_URL)
+        endpoint: String,
+    },
+
+    /// Verify an archive's signature and checksums offline
+    Verify {
+        /// Archive to verify, e.g. output/plugin/<publisher>.<name>.tar.gz
+        archive: String,
+        #[arg(long)]
+        /// Signature file, devapack or minisign (defaults to <archive>.sig, then <archive>.minisig)
+        sig: Option<String>,
+        #[arg(long)]
+        /// Signer's public key, as base64, a key file or a minisign public key (defaults to the publisher's trusted keys, then the local keys)
+        pubkey: Option<String>,
+        #[arg(long)]
+        /// Publisher whose trusted keys must have signed the archive (defaults to the one in the archive manifest)
+        publisher: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Trust the key named in the signature file without asking when the publisher has no trusted key yet
+        trust_new: bool,
+    },
 }
 
 #[derive(Subcommand)]
 enum BankCommands {
     /// Scaffold a new bank
-    Create {},
+    Create {
+        #[arg(long)]
+        /// Bank name (prompted for when missing)
+        name: Option<String>,
+        #[arg(long)]
+        /// Bank publisher (prompted for when missing)
+        publisher: Option<String>,
+        #[arg(long)]
+        /// Bank description
+        description: Option<String>,
+        #[arg(short, long, default_value_t = false)]
+        /// Create without asking for confirmation
+        yes: bool,
+        #[arg(long)]
+        /// Template directory, or the name of a template in .devalang/templates/bank or ~/.devalang/templates/bank
+        template: Option<String>,
+    },
 
     /// Build banks
     Build {
         /// Relative path OR alias bank.<bankId>. Leave empty to build all.
         path: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Sign the archive with the local key, writing <archive>.sig
+        sign: bool,
+        #[arg(long, default_value_t = false)]
+        /// Fail unless <archive>.sig exists and matches the archive
+        require_signature: bool,
+        #[arg(long, default_value_t = false)]
+        /// Also write a minisign signature, <archive>.minisig (implies --sign)
+        minisign: bool,
+        #[arg(long, default_value_t = false)]
+        /// When building all, rebuild banks even if unchanged since their last build
+        force: bool,
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        /// When building all, number of banks built at once
+        jobs: u16,
     },
 
     /// List available banks
@@ -82,7 +163,35 @@ enum BankCommands {
 #[derive(Subcommand)]
 enum PluginCommands {
     /// Scaffold a new plugin
-    Create {},
+    Create {
+        #[arg(long)]
+        /// Plugin name (prompted for when missing)
+        name: Option<String>,
+        #[arg(long)]
+        /// Plugin publisher (prompted for when missing)
+        publisher: Option<String>,
+        #[arg(long)]
+        /// Plugin description
+        description: Option<String>,
+        #[arg(long, conflicts_with = "template")]
+        /// Built-in preset: empty | synth | fx | sequencer
+        preset: Option<String>,
+        #[arg(short, long, default_value_t = false)]
+        /// Create without asking for confirmation
+        yes: bool,
+        #[arg(long)]
+        /// Pin the devalang crate version used by the plugin (e.g. 0.2.0)
+        devalang_version: Option<String>,
+        #[arg(long, conflicts_with = "devalang_version")]
+        /// Depend on a local devalang checkout instead of crates.io
+        devalang_path: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Do not query crates.io; use the pinned or cached devalang version
+        offline: bool,
+        #[arg(long)]
+        /// Template directory, or the name of a template in .devalang/templates/plugin or ~/.devalang/templates/plugin
+        template: Option<String>,
+    },
 
     /// Build plugins
     Build {
@@ -92,8 +201,26 @@ enum PluginCommands {
         /// Whether to build the release version
         release: bool,
         #[arg(long, default_value_t = false)]
-        /// Require artifact to be signed (will error if no signature produced)
+        /// Sign the archive with the local key, writing <archive>.sig
+        sign: bool,
+        #[arg(long, default_value_t = false)]
+        /// Fail unless <archive>.sig exists and matches the archive
         require_signature: bool,
+        #[arg(long, default_value_t = false)]
+        /// Also write a minisign signature, <archive>.minisig (implies --sign)
+        minisign: bool,
+        #[arg(long, default_value = "wasm")]
+        /// Binaries to build and package: native | wasm | all
+        target: String,
+        #[arg(long, default_value_t = false)]
+        /// Also package the sources as <publisher>.<name>.src.tar.gz
+        with_source: bool,
+        #[arg(long, default_value_t = false)]
+        /// When building all, rebuild plugins even if unchanged since their last build
+        force: bool,
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        /// When building all, number of plugins built at once
+        jobs: u16,
     },
 
     /// List available plugins
@@ -106,12 +233,148 @@ enum PluginCommands {
         /// Bump type: major | minor | patch
         bump: String,
     },
+
+    /// Delete a generated plugin and its build artifacts
+    Delete {
+        /// Plugin identifier: <publisher>.<name>
+        id: String,
+        #[arg(short, long, default_value_t = false)]
+        /// Delete without asking for confirmation
+        yes: bool,
+    },
+
+    /// Manage all plugins as members of one Cargo workspace with a shared target dir
+    Workspace {},
+
+    /// Benchmark a built plugin's audio exports
+    Bench {
+        /// Relative path OR alias plugin.<pluginId>
+        path: String,
+        #[arg(long, default_value_t = 20)]
+        /// Number of measured iterations per configuration
+        iterations: usize,
+        #[arg(long, default_value_t = 256)]
+        /// Number of blocks rendered per iteration
+        blocks: usize,
+        #[arg(long, value_delimiter = ',', default_values_t = vec![44100, 48000, 96000], value_parser = clap::value_parser!(u32).range(1..))]
+        /// Sample rates to test (comma-separated)
+        sample_rates: Vec<u32>,
+        #[arg(long, value_delimiter = ',', default_values_t = vec![64, 128, 256, 512, 1024], value_parser = clap::value_parser!(u32).range(1..))]
+        /// Buffer sizes in frames to test (comma-separated)
+        buffer_sizes: Vec<u32>,
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+        /// Number of interleaved output channels
+        channels: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCommands {
+    /// Create the signing key (~/.devalang/keys/ed25519.key), encrypted with a passphrase
+    Generate {
+        #[arg(long, default_value_t = false)]
+        /// Store the key unencrypted
+        no_passphrase: bool,
+    },
+
+    /// Show the key's path, format, public key and fingerprint
+    Show {},
+
+    /// Print the base64 public key
+    ExportPublic {
+        #[arg(short, long)]
+        /// Write the key to this file instead of stdout
+        output: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Export as a minisign public key file (minisign.pub)
+        minisign: bool,
+    },
+
+    /// Import a 32-byte secret or 64-byte keypair, raw or base64
+    Import {
+        /// Key file to import
+        path: String,
+        #[arg(long, default_value_t = false)]
+        /// Replace the current key (it is retired, not deleted)
+        force: bool,
+        #[arg(long, default_value_t = false)]
+        /// Store a raw key unencrypted
+        no_passphrase: bool,
+    },
+
+    /// Replace the key with a new one, keeping the old one to verify older signatures
+    Rotate {
+        #[arg(long, default_value_t = false)]
+        /// Store the new key unencrypted
+        no_passphrase: bool,
+    },
+
+    /// Set or change the key passphrase, encrypting a key stored unencrypted
+    Passphrase {
+        #[arg(long, default_value_t = false)]
+        /// Remove the passphrase and store the key unencrypted
+        remove: bool,
+    },
+
+    /// Upload the public key to Forge
+    Register {},
+}
+
+#[derive(Subcommand)]
+enum TrustCommands {
+    /// Trust a public key for a publisher
+    Add {
+        /// Publisher identifier
+        publisher: String,
+        /// Public key, as base64, a key file or a minisign public key
+        key: String,
+    },
+
+    /// Stop trusting a publisher's keys
+    Remove {
+        /// Publisher identifier
+        publisher: String,
+        #[arg(long)]
+        /// Fingerprint of the key to remove (defaults to all of the publisher's keys)
+        key: Option<String>,
+    },
+
+    /// List the trusted keys
+    List {
+        /// Only list this publisher's keys
+        publisher: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 enum PublisherCommands {
     /// Create a new publisher
-    Create {},
+    Create {
+        #[arg(long)]
+        /// Unique publisher identifier (prompted for when missing)
+        identifier: Option<String>,
+        #[arg(long)]
+        /// Display name (prompted for when missing)
+        display_name: Option<String>,
+        #[arg(long)]
+        /// Publisher description (prompted for when missing)
+        description: Option<String>,
+        #[arg(long)]
+        /// Logo URL
+        logo_url: Option<String>,
+        #[arg(long)]
+        /// Banner URL
+        banner_url: Option<String>,
+        #[arg(long)]
+        /// Country code, e.g. US, GB, FR
+        country_code: Option<String>,
+        #[arg(long)]
+        /// Comma-separated tags
+        tags: Option<String>,
+        #[arg(short, long, default_value_t = false)]
+        /// Create without asking for confirmation
+        yes: bool,
+    },
 
     /// Update publisher details
     Update { name: Option<String> },
@@ -147,38 +410,137 @@ async fn main() -> io::Result<()> {
         .map_err(|_| std::io::Error::other("Current directory contains invalid UTF-8"))?;
 
     match cli.command {
-        Commands::Submit {} => {
-            if let Err(e) = addon::submit::prompt::prompt_submit_addon(&cwd).await {
+        Commands::Submit { legacy_signatures } => {
+            if let Err(e) =
+                addon::submit::prompt::prompt_submit_addon(&cwd, legacy_signatures).await
+            {
                 return Err(io::Error::other(e));
             }
 
             Ok(())
         }
 
-        Commands::Update {} => {
-            if let Err(e) = addon::update::prompt::prompt_update_addon(&cwd).await {
+        Commands::Update { legacy_signatures } => {
+            if let Err(e) =
+                addon::update::prompt::prompt_update_addon(&cwd, legacy_signatures).await
+            {
                 return Err(io::Error::other(e));
             }
 
             Ok(())
         }
 
+        Commands::Key { command } => {
+            let res = match command {
+                KeyCommands::Generate { no_passphrase } => key::generate_key(no_passphrase),
+                KeyCommands::Show {} => key::show_key(),
+                KeyCommands::ExportPublic { output, minisign } => {
+                    key::export_public_key(output.as_deref(), minisign)
+                }
+                KeyCommands::Import {
+                    path,
+                    force,
+                    no_passphrase,
+                } => key::import_key(&path, force, no_passphrase),
+                KeyCommands::Rotate { no_passphrase } => key::rotate_key(no_passphrase),
+                KeyCommands::Passphrase { remove } => key::change_passphrase(remove),
+                KeyCommands::Register {} => key::register_key().await,
+            };
+            if let Err(e) = res {
+                return Err(io::Error::other(e));
+            }
+
+            Ok(())
+        }
+
+        Commands::Trust { command } => {
+            let res = match command {
+                TrustCommands::Add { publisher, key } => trust::add_key(&cwd, &publisher, &key),
+                TrustCommands::Remove { publisher, key } => {
+                    trust::remove_key(&publisher, key.as_deref())
+                }
+                TrustCommands::List { publisher } => trust::list_keys(publisher.as_deref()),
+            };
+            if let Err(e) = res {
+                return Err(io::Error::other(e));
+            }
+
+            Ok(())
+        }
+
+        Commands::Doctor { endpoint } => {
+            if let Err(e) = doctor::run_doctor(&cwd, &endpoint).await {
+                return Err(io::Error::other(e));
+            }
+
+            Ok(())
+        }
+
+        Commands::Verify {
+            archive,
+            sig,
+            pubkey,
+            publisher,
+            trust_new,
+        } => match verify::run_verify(
+            &cwd,
+            &archive,
+            sig.as_deref(),
+            pubkey.as_deref(),
+            publisher.as_deref(),
+            trust_new,
+        ) {
+            Ok(verify::Verdict::Valid) => Ok(()),
+            Ok(verify::Verdict::Tampered) => std::process::exit(verify::EXIT_TAMPERED),
+            Ok(verify::Verdict::Untrusted) => std::process::exit(1),
+            Err(e) => Err(io::Error::other(e)),
+        },
+
         Commands::Bank { command } => match command {
-            BankCommands::Create {} => {
-                if let Err(e) = addon::bank::prompt::prompt_bank_addon(&cwd).await {
+            BankCommands::Create {
+                name,
+                publisher,
+                description,
+                yes,
+                template,
+            } => {
+                let options = addon::bank::prompt::BankCreateOptions {
+                    name,
+                    publisher,
+                    description,
+                    template,
+                    yes,
+                };
+                if let Err(e) = addon::bank::prompt::prompt_bank_addon(&cwd, options).await {
                     return Err(io::Error::other(e));
                 }
 
                 Ok(())
             }
 
-            BankCommands::Build { path } => {
+            BankCommands::Build {
+                path,
+                sign,
+                require_signature,
+                minisign,
+                force,
+                jobs,
+            } => {
+                let signature = SignatureOptions {
+                    sign: sign || minisign,
+                    require: require_signature,
+                    minisign,
+                };
                 match path {
                     Some(p) => {
+                        // Unlock the key before the build spinners start
+                        if signature.sign {
+                            builder::sign::prepare_key().map_err(io::Error::other)?;
+                        }
                         let cwd_clone = cwd.clone();
                         let p_clone = p.clone();
                         let res = tokio::task::spawn_blocking(move || {
-                            bank_builder::build_bank(&p_clone, &cwd_clone)
+                            bank_builder::build_bank(&p_clone, &cwd_clone, signature)
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -189,7 +551,12 @@ async fn main() -> io::Result<()> {
                     None => {
                         let cwd_clone = cwd.clone();
                         let res = tokio::task::spawn_blocking(move || {
-                            bank_builder::build_all_banks(&cwd_clone)
+                            bank_builder::build_all_banks(
+                                &cwd_clone,
+                                signature,
+                                force,
+                                jobs as usize,
+                            )
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -228,8 +595,33 @@ async fn main() -> io::Result<()> {
         },
 
         Commands::Plugin { command } => match command {
-            PluginCommands::Create {} => {
-                if let Err(e) = addon::plugin::prompt::prompt_plugin_addon(&cwd).await {
+            PluginCommands::Create {
+                name,
+                publisher,
+                description,
+                preset,
+                yes,
+                devalang_version,
+                devalang_path,
+                offline,
+                template,
+            } => {
+                let devalang = addon::plugin::scaffold::DevalangDependency {
+                    version: devalang_version,
+                    path: devalang_path,
+                    offline,
+                };
+                let options = addon::plugin::prompt::PluginCreateOptions {
+                    name,
+                    publisher,
+                    description,
+                    preset,
+                    template,
+                    yes,
+                };
+                if let Err(e) =
+                    addon::plugin::prompt::prompt_plugin_addon(&cwd, devalang, options).await
+                {
                     return Err(io::Error::other(e));
                 }
 
@@ -239,16 +631,40 @@ async fn main() -> io::Result<()> {
             PluginCommands::Build {
                 path,
                 release,
+                sign,
                 require_signature,
+                minisign,
+                target,
+                with_source,
+                force,
+                jobs,
             } => {
+                let signature = SignatureOptions {
+                    sign: sign || minisign,
+                    require: require_signature,
+                    minisign,
+                };
+                let target =
+                    plugin_builder::PluginTarget::parse(&target).map_err(io::Error::other)?;
                 match path {
                     Some(p) => {
+                        // Unlock the key before the build spinners start
+                        if signature.sign {
+                            builder::sign::prepare_key().map_err(io::Error::other)?;
+                        }
                         let cwd_clone = cwd.clone();
                         let p_clone = p.clone();
                         let rel = release;
-                        let req_sig = require_signature;
                         let res = tokio::task::spawn_blocking(move || {
-                            plugin_builder::build_plugin(&p_clone, &rel, &cwd_clone, req_sig, true)
+                            plugin_builder::build_plugin(
+                                &p_clone,
+                                &rel,
+                                &cwd_clone,
+                                signature,
+                                true,
+                                target,
+                                with_source,
+                            )
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -259,9 +675,16 @@ async fn main() -> io::Result<()> {
                     None => {
                         let cwd_clone = cwd.clone();
                         let rel = release;
-                        let req_sig = require_signature;
                         let res = tokio::task::spawn_blocking(move || {
-                            plugin_builder::build_all_plugins(&rel, &cwd_clone, req_sig)
+                            plugin_builder::build_all_plugins(
+                                &rel,
+                                &cwd_clone,
+                                signature,
+                                target,
+                                with_source,
+                                force,
+                                jobs as usize,
+                            )
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -273,6 +696,20 @@ async fn main() -> io::Result<()> {
 
                 Ok(())
             }
+            PluginCommands::Delete { id, yes } => {
+                if let Err(e) = addon::plugin::manage::delete_plugin(&cwd, &id, yes) {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+            PluginCommands::Workspace {} => {
+                if let Err(e) = addon::plugin::workspace::init_plugins_workspace(&cwd) {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
             PluginCommands::List {} => {
                 if let Err(e) = addon::plugin::manage::list_plugins(&cwd) {
                     eprintln!("Error listing plugins: {}", e);
@@ -285,13 +722,59 @@ async fn main() -> io::Result<()> {
                     eprintln!("Error bumping version: {}", e);
                 }
 
+                Ok(())
+            }
+            PluginCommands::Bench {
+                path,
+                iterations,
+                blocks,
+                sample_rates,
+                buffer_sizes,
+                channels,
+            } => {
+                let options = addon::plugin::bench::BenchOptions {
+                    iterations,
+                    blocks,
+                    sample_rates,
+                    buffer_sizes,
+                    channels,
+                };
+                let cwd_clone = cwd.clone();
+                let res = tokio::task::spawn_blocking(move || {
+                    addon::plugin::bench::bench_plugin(&path, &cwd_clone, &options)
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
                 Ok(())
             }
         },
 
         Commands::Publisher { command } => match command {
-            PublisherCommands::Create {} => {
-                if let Err(e) = publisher::create::prompt_create_publisher().await {
+            PublisherCommands::Create {
+                identifier,
+                display_name,
+                description,
+                logo_url,
+                banner_url,
+                country_code,
+                tags,
+                yes,
+            } => {
+                let options = publisher::create::PublisherCreateOptions {
+                    identifier,
+                    display_name,
+                    description,
+                    logo_url,
+                    banner_url,
+                    country_code,
+                    tags,
+                    yes,
+                };
+                if let Err(e) = publisher::create::prompt_create_publisher(options).await {
                     return Err(io::Error::other(e));
                 }
 
@@ -1,117 +1,115 @@
 use crate::{
-    publisher::request::post_create_publisher_to_forge_api,
+    forge::ForgeClient,
     types::publisher::PublisherInfo,
-    utils::{logger::Logger, spinner::with_spinner},
+    utils::{logger::Logger, prompt, spinner::with_spinner},
 };
 
-pub async fn prompt_create_publisher() -> Result<(), String> {
+/// Values given on the command line for `publisher create`; missing ones are prompted for.
+#[derive(Debug, Clone, Default)]
+pub struct PublisherCreateOptions {
+    pub identifier: Option<String>,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub country_code: Option<String>,
+    pub tags: Option<String>,
+    /// Skip the confirmation prompt
+    pub yes: bool,
+}
+
+/// Prompts for publisher details and creates the publisher on the Forge.
+///
+/// ### Parameters
+/// - `options`: Values given as flags; only the missing ones are prompted for
+///
+pub async fn prompt_create_publisher(options: PublisherCreateOptions) -> Result<(), String> {
     println!();
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!("Devalang Publisher Creator");
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!();
 
-    let identifier = match inquire::Text::new("Enter the publisher identifier:")
-        .with_default("mypublisher")
-        .prompt()
-    {
-        Ok(identifier) => identifier.to_string(),
-        Err(e) => {
-            return Err(format!("Failed to prompt for publisher identifier: {}", e));
-        }
-    };
-
-    let display_name = match inquire::Text::new("Enter the publisher display name:")
-        .with_default("My Publisher")
-        .prompt()
-    {
-        Ok(display_name) => display_name.to_string(),
-        Err(e) => {
-            return Err(format!(
-                "Failed to prompt for publisher display name: {}",
-                e
-            ));
-        }
-    };
-
-    let description = match inquire::Text::new("Enter the publisher description:")
-        .with_default("A description of my publisher")
-        .prompt()
-    {
-        Ok(description) => description.to_string(),
-        Err(e) => {
-            return Err(format!("Failed to prompt for publisher description: {}", e));
-        }
-    };
-
-    let logo_url = match inquire::Text::new("Enter the publisher logo URL (optional):")
-        .with_default("")
-        .prompt()
-    {
-        Ok(logo_url) => {
-            let url = logo_url.to_string();
-            if url.trim().is_empty() {
-                None
-            } else {
-                Some(url)
-            }
-        }
-        Err(e) => {
-            return Err(format!("Failed to prompt for publisher logo URL: {}", e));
-        }
-    };
-
-    let banner_url = match inquire::Text::new("Enter the publisher banner URL (optional):")
-        .with_default("")
-        .prompt()
-    {
-        Ok(banner_url) => {
-            let url = banner_url.to_string();
-            if url.trim().is_empty() {
-                None
-            } else {
-                Some(url)
-            }
-        }
-        Err(e) => {
-            return Err(format!("Failed to prompt for publisher banner URL: {}", e));
+    let identifier = prompt::text(
+        options.identifier,
+        "identifier",
+        "Enter the publisher identifier:",
+        "mypublisher",
+        true,
+    )?;
+
+    let display_name = prompt::text(
+        options.display_name,
+        "display-name",
+        "Enter the publisher display name:",
+        "My Publisher",
+        true,
+    )?;
+
+    let description = prompt::text(
+        options.description,
+        "description",
+        "Enter the publisher description:",
+        "A description of my publisher",
+        true,
+    )?;
+
+    let logo_url = prompt::text(
+        options.logo_url,
+        "logo-url",
+        "Enter the publisher logo URL (optional):",
+        "",
+        false,
+    )
+    .map(|url| {
+        if url.trim().is_empty() {
+            None
+        } else {
+            Some(url)
         }
-    };
-
-    let country_code =
-        match inquire::Text::new("Enter the publisher country code (e.g., US, GB, FR) (optional):")
-            .with_default("")
-            .prompt()
-        {
-            Ok(country_code) => country_code.to_string(),
-            Err(e) => {
-                return Err(format!(
-                    "Failed to prompt for publisher country code: {}",
-                    e
-                ));
-            }
-        };
-
-    let tags = match inquire::Text::new("Enter tags for the publisher (comma-separated, optional):")
-        .with_default("")
-        .prompt()
-    {
-        Ok(tags) => {
-            let input = tags.to_string();
-            if input.trim().is_empty() {
-                Vec::new()
-            } else {
-                input
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            }
+    })?;
+
+    let banner_url = prompt::text(
+        options.banner_url,
+        "banner-url",
+        "Enter the publisher banner URL (optional):",
+        "",
+        false,
+    )
+    .map(|url| {
+        if url.trim().is_empty() {
+            None
+        } else {
+            Some(url)
         }
-        Err(e) => {
-            return Err(format!("Failed to prompt for publisher tags: {}", e));
+    })?;
+
+    let country_code = prompt::text(
+        options.country_code,
+        "country-code",
+        "Enter the publisher country code (e.g., US, GB, FR) (optional):",
+        "",
+        false,
+    )?;
+
+    let tags = prompt::text(
+        options.tags,
+        "tags",
+        "Enter tags for the publisher (comma-separated, optional):",
+        "",
+        false,
+    )
+    .map(|input| {
+        if input.trim().is_empty() {
+            Vec::new()
+        } else {
+            input
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
         }
-    };
+    })?;
 
     let publisher_payload = PublisherInfo {
         identifier,
@@ -167,15 +165,7 @@ pub async fn prompt_create_publisher() -> Result<(), String> {
     );
     println!();
 
-    let confirm = match inquire::Confirm::new("Are all details correct?")
-        .with_default(true)
-        .prompt()
-    {
-        Ok(confirm) => confirm,
-        Err(e) => {
-            return Err(format!("Failed to prompt for confirmation: {}", e));
-        }
-    };
+    let confirm = prompt::confirm(options.yes, "Are all details correct?")?;
 
     if !confirm {
         println!("Publisher creation cancelled by user.");
@@ -184,7 +174,10 @@ pub async fn prompt_create_publisher() -> Result<(), String> {
 
     let create_publisher_spinner = with_spinner("Creating publisher...");
 
-    if let Err(e) = post_create_publisher_to_forge_api(&publisher_payload).await {
+    if let Err(e) = ForgeClient::authenticated()?
+        .create_publisher(&publisher_payload)
+        .await
+    {
         return Err(format!("Failed to create publisher: {}", e));
     }
 
@@ -1,7 +1,7 @@
-use crate::publisher::request::get_user_publishers;
+use crate::forge::ForgeClient;
 
 pub async fn list_publishers() -> Result<(), String> {
-    let publishers = match get_user_publishers().await {
+    let publishers = match ForgeClient::authenticated()?.list_publishers().await {
         Ok(pubs) => pubs,
         Err(e) => {
             return Err(format!("Failed to fetch publishers: {}", e));
@@ -1,4 +1,3 @@
 pub mod create;
 pub mod list;
-pub mod request;
 pub mod update;
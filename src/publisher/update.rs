@@ -1,5 +1,5 @@
 use crate::{
-    publisher::request::post_update_publisher_to_forge_api,
+    forge::ForgeClient,
     types::publisher::PublisherInfoUpdate,
     utils::{logger::Logger, spinner::with_spinner},
 };
@@ -11,7 +11,8 @@ pub async fn prompt_update_publisher(name: Option<String>) -> Result<(), String>
     println!("⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯");
     println!();
 
-    let user_publishers = match crate::publisher::request::get_user_publishers().await {
+    let client = ForgeClient::authenticated()?;
+    let user_publishers = match client.list_publishers().await {
         Ok(publishers) => publishers,
         Err(e) => {
             return Err(format!("Failed to fetch user publishers: {}", e));
@@ -232,8 +233,9 @@ pub async fn prompt_update_publisher(name: Option<String>) -> Result<(), String>
 
     let update_spinner = with_spinner("Updating publisher...");
 
-    if let Err(e) =
-        post_update_publisher_to_forge_api(&selected_identifier, &publisher_payload).await
+    if let Err(e) = client
+        .update_publisher(&selected_identifier, &publisher_payload)
+        .await
     {
         return Err(format!("Failed to update publisher: {}", e));
     }
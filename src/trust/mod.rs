@@ -0,0 +1,104 @@
+pub mod store;
+
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    signing,
+};
+use store::TrustStore;
+
+/// Pins a public key for a publisher, so its addons must be signed with a pinned key.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `publisher`: The publisher identifier
+/// - `key`: The public key: base64, minisign, or a key file
+///
+pub fn add_key(cwd: &str, publisher: &str, key: &str) -> Result<(), String> {
+    let public = signing::public_key_from_arg(cwd, key)?;
+    let key_id = signing::fingerprint(&public);
+    let mut store = TrustStore::load()?;
+    if !store.add(publisher, &public, "manual") {
+        Logger::new().log_message(
+            LogLevel::Info,
+            &format!("Key {} is already trusted for '{}'", key_id, publisher),
+        );
+        return Ok(());
+    }
+    store.save()?;
+    let pinned = store.keys(publisher).len();
+    let key_line = format!("Key: {}", key_id);
+    let count_line = format!("Trusted keys for '{}': {}", publisher, pinned);
+    Logger::new().log_message_with_trace(
+        LogLevel::Success,
+        &format!("Key trusted for '{}'", publisher),
+        vec![&key_line, &count_line],
+    );
+    Ok(())
+}
+
+/// Unpins one key of a publisher, or all of them.
+///
+/// ### Parameters
+/// - `publisher`: The publisher identifier
+/// - `key_id`: The fingerprint of the key to remove, or `None` for every key
+///
+pub fn remove_key(publisher: &str, key_id: Option<&str>) -> Result<(), String> {
+    let mut store = TrustStore::load()?;
+    let removed = store.remove(publisher, key_id);
+    if removed == 0 {
+        return Err(match key_id {
+            Some(id) => format!("Key {} is not trusted for '{}'", id, publisher),
+            None => format!("No trusted keys for '{}'", publisher),
+        });
+    }
+    store.save()?;
+    Logger::new().log_message(
+        LogLevel::Success,
+        &format!(
+            "Removed {} trusted key(s) for '{}'; the next key seen for it will be asked about again",
+            removed, publisher
+        ),
+    );
+    Ok(())
+}
+
+/// Prints the trusted keys, of every publisher or of one.
+///
+/// ### Parameters
+/// - `publisher`: Only list this publisher's keys
+///
+pub fn list_keys(publisher: Option<&str>) -> Result<(), String> {
+    let store = TrustStore::load()?;
+    let logger = Logger::new();
+    let mut shown = 0;
+    for (name, keys) in &store.publishers {
+        if publisher.is_some_and(|p| p != name) {
+            continue;
+        }
+        let lines: Vec<String> = keys
+            .iter()
+            .map(|k| {
+                format!(
+                    "{} ({}, added {}): {}",
+                    k.key_id, k.source, k.added_at, k.public_key
+                )
+            })
+            .collect();
+        let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        logger.log_message_with_trace(LogLevel::Info, name, refs);
+        shown += 1;
+    }
+    if shown == 0 {
+        logger.log_message(
+            LogLevel::Info,
+            &format!(
+                "No trusted keys{} in {}",
+                publisher
+                    .map(|p| format!(" for '{}'", p))
+                    .unwrap_or_default(),
+                store::store_path()?.display()
+            ),
+        );
+    }
+    Ok(())
+}
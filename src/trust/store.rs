@@ -0,0 +1,171 @@
+use crate::utils::{prompt, signing};
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A publisher key the user trusts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    /// Base64 Ed25519 public key
+    pub public_key: String,
+    /// Fingerprint of `public_key`
+    pub key_id: String,
+    /// RFC 3339 time the key was pinned
+    pub added_at: String,
+    /// How the key was pinned: `manual` (`devapack trust add`) or `first-use`
+    pub source: String,
+}
+
+/// The keyring at `~/.devalang/trusted_keys`: the pinned keys of each publisher.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(default)]
+    pub publishers: BTreeMap<String, Vec<TrustedKey>>,
+}
+
+/// Outcome of checking an addon signer against the pinned keys of its publisher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trust {
+    /// The signer is a pinned key of the publisher
+    Pinned,
+    /// The publisher had no pinned key and the signer was pinned now
+    PinnedOnFirstUse,
+    /// The publisher has no pinned key and the signer was not pinned
+    Unpinned,
+    /// The publisher is pinned to other keys, listed by fingerprint
+    Mismatch(Vec<String>),
+}
+
+pub fn store_path() -> Result<PathBuf, String> {
+    let home = crate::utils::fs::get_user_home()?;
+    Ok(home.join(".devalang").join("trusted_keys"))
+}
+
+impl TrustStore {
+    /// Reads the keyring, empty when the file does not exist yet.
+    pub fn load() -> Result<Self, String> {
+        let path = store_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let txt = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&txt).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// Writes the keyring, readable by the user alone like the key files.
+    pub fn save(&self) -> Result<(), String> {
+        let txt =
+            toml::to_string(self).map_err(|e| format!("Failed to serialize trust store: {}", e))?;
+        signing::write_private_file(&store_path()?, txt.as_bytes())
+    }
+
+    /// Pinned keys of a publisher, skipping entries that do not parse.
+    ///
+    /// ### Parameters
+    /// - `publisher`: The publisher identifier
+    ///
+    pub fn keys(&self, publisher: &str) -> Vec<PublicKey> {
+        self.publishers
+            .get(publisher)
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|k| signing::decode_public_key(&k.public_key).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Pins a key for a publisher, returning `false` when it already was.
+    ///
+    /// ### Parameters
+    /// - `publisher`: The publisher identifier
+    /// - `public`: The key to pin
+    /// - `source`: How the key was pinned, `manual` or `first-use`
+    ///
+    pub fn add(&mut self, publisher: &str, public: &PublicKey, source: &str) -> bool {
+        let key_id = signing::fingerprint(public);
+        let keys = self.publishers.entry(publisher.to_string()).or_default();
+        if keys.iter().any(|k| k.key_id == key_id) {
+            return false;
+        }
+        keys.push(TrustedKey {
+            public_key: general_purpose::STANDARD.encode(public.to_bytes()),
+            key_id,
+            added_at: chrono::Utc::now().to_rfc3339(),
+            source: source.to_string(),
+        });
+        true
+    }
+
+    /// Unpins one key of a publisher, or all of them, returning how many were removed.
+    ///
+    /// ### Parameters
+    /// - `publisher`: The publisher identifier
+    /// - `key_id`: The fingerprint of the key to remove, or `None` for every key
+    ///
+    pub fn remove(&mut self, publisher: &str, key_id: Option<&str>) -> usize {
+        let Some(keys) = self.publishers.get_mut(publisher) else {
+            return 0;
+        };
+        let before = keys.len();
+        match key_id {
+            Some(id) => keys.retain(|k| !k.key_id.eq_ignore_ascii_case(id)),
+            None => keys.clear(),
+        }
+        let removed = before - keys.len();
+        if keys.is_empty() {
+            self.publishers.remove(publisher);
+        }
+        removed
+    }
+}
+
+/// Checks an addon signer against the keys pinned for its publisher.
+///
+/// When the publisher has no pinned key yet, offers to pin the signer (trust on first use):
+/// it asks in a terminal, pins without asking when `trust_new` is set, and otherwise
+/// leaves the publisher unpinned. `Trust::Unpinned` vouches for nothing: callers must
+/// reject the signer unless the user supplied the key themselves.
+///
+/// ### Parameters
+/// - `publisher`: The publisher identifier the addon claims
+/// - `signer`: The key the addon's signature was made with
+/// - `trust_new`: Pin the signer of a publisher without pinned keys without asking
+///
+pub fn check_signer(publisher: &str, signer: &PublicKey, trust_new: bool) -> Result<Trust, String> {
+    let mut store = TrustStore::load()?;
+    let pinned = store.keys(publisher);
+    if pinned.contains(signer) {
+        return Ok(Trust::Pinned);
+    }
+    if !pinned.is_empty() {
+        return Ok(Trust::Mismatch(
+            pinned.iter().map(signing::fingerprint).collect(),
+        ));
+    }
+
+    let accept = if trust_new {
+        true
+    } else if prompt::is_interactive() {
+        inquire::Confirm::new(&format!(
+            "Publisher '{}' has no trusted key yet. Trust key {} for it from now on?",
+            publisher,
+            signing::fingerprint(signer)
+        ))
+        .with_default(false)
+        .prompt()
+        .map_err(|e| format!("Failed to prompt for confirmation: {}", e))?
+    } else {
+        false
+    };
+    if !accept {
+        return Ok(Trust::Unpinned);
+    }
+    store.add(publisher, signer, "first-use");
+    store.save()?;
+    Ok(Trust::PinnedOnFirstUse)
+}
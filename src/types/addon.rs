@@ -12,6 +12,7 @@ pub struct AddonMetadata {
     pub version: String,
     pub access: String,
     pub publisher: String,
+    pub engine: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -24,4 +25,5 @@ pub struct AddonSubmissionData {
     pub version: String,
     pub access: String,
     pub files: Vec<String>,
+    pub engine: Option<String>,
 }
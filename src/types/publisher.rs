@@ -1,13 +1,19 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublisherInfo {
     pub identifier: String,
+    #[serde(default, deserialize_with = "string_or_null")]
     pub display_name: String,
+    #[serde(default, deserialize_with = "string_or_null")]
     pub description: String,
+    #[serde(default)]
     pub logo_url: Option<String>,
+    #[serde(default)]
     pub banner_url: Option<String>,
+    #[serde(default)]
     pub country_code: Option<String>,
+    #[serde(default, deserialize_with = "tag_list")]
     pub tags: Vec<String>,
 }
 
@@ -20,3 +26,23 @@ pub struct PublisherInfoUpdate {
     pub country_code: Option<String>,
     pub tags: Vec<String>,
 }
+
+fn string_or_null<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Reads tags sent either as an array or as a string holding a JSON array, like `"[\"a\"]"`.
+fn tag_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let tags = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => serde_json::from_str(&s).unwrap_or_default(),
+        value => value,
+    };
+    Ok(tags
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default())
+}
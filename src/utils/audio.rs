@@ -0,0 +1,133 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Channel count and length of an audio file, read from its header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioInfo {
+    pub channels: u16,
+    pub duration_secs: f64,
+}
+
+/// Reads the channel count and duration of a WAV, AIFF or FLAC file.
+///
+/// Only headers are parsed, nothing is decoded. Returns `None` for other formats
+/// (mp3, ogg) and for files whose header cannot be understood.
+///
+/// ### Parameters
+/// - `path`: The audio file
+///
+pub fn probe(path: &Path) -> Option<AudioInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut magic = [0u8; 12];
+    file.read_exact(&mut magic).ok()?;
+    match (&magic[0..4], &magic[8..12]) {
+        (b"RIFF", b"WAVE") => probe_wav(&mut file),
+        (b"FORM", b"AIFF") | (b"FORM", b"AIFC") => probe_aiff(&mut file),
+        (b"fLaC", _) => {
+            file.seek(SeekFrom::Start(4)).ok()?;
+            probe_flac(&mut file)
+        }
+        _ => None,
+    }
+}
+
+/// Walks the RIFF chunks after the `WAVE` tag for `fmt ` and `data`.
+fn probe_wav(file: &mut File) -> Option<AudioInfo> {
+    let mut channels: Option<u16> = None;
+    let mut byte_rate: Option<u32> = None;
+    loop {
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header).ok()?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        match &header[0..4] {
+            b"fmt " => {
+                let mut fmt = [0u8; 16];
+                file.read_exact(&mut fmt).ok()?;
+                channels = Some(u16::from_le_bytes([fmt[2], fmt[3]]));
+                byte_rate = Some(u32::from_le_bytes([fmt[8], fmt[9], fmt[10], fmt[11]]));
+                skip_chunk(file, size as i64 - 16, size)?;
+            }
+            b"data" => {
+                let (channels, byte_rate) = (channels?, byte_rate?);
+                if channels == 0 || byte_rate == 0 {
+                    return None;
+                }
+                return Some(AudioInfo {
+                    channels,
+                    duration_secs: size as f64 / byte_rate as f64,
+                });
+            }
+            _ => skip_chunk(file, size as i64, size)?,
+        }
+    }
+}
+
+/// Walks the IFF chunks after the `AIFF`/`AIFC` tag for `COMM`.
+fn probe_aiff(file: &mut File) -> Option<AudioInfo> {
+    loop {
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header).ok()?;
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        if &header[0..4] != b"COMM" {
+            skip_chunk(file, size as i64, size)?;
+            continue;
+        }
+        let mut comm = [0u8; 18];
+        file.read_exact(&mut comm).ok()?;
+        let channels = u16::from_be_bytes([comm[0], comm[1]]);
+        let frames = u32::from_be_bytes([comm[2], comm[3], comm[4], comm[5]]);
+        let sample_rate = extended_to_f64(&comm[8..18]);
+        if channels == 0 || sample_rate <= 0.0 {
+            return None;
+        }
+        return Some(AudioInfo {
+            channels,
+            duration_secs: frames as f64 / sample_rate,
+        });
+    }
+}
+
+/// Reads the STREAMINFO block, which the FLAC format requires to come first.
+fn probe_flac(file: &mut File) -> Option<AudioInfo> {
+    let mut block_header = [0u8; 4];
+    file.read_exact(&mut block_header).ok()?;
+    if block_header[0] & 0x7f != 0 {
+        return None;
+    }
+    let mut info = [0u8; 18];
+    file.read_exact(&mut info).ok()?;
+    // 20 bits sample rate, 3 bits channels - 1, 5 bits bits per sample - 1, 36 bits total samples
+    let sample_rate =
+        ((info[10] as u32) << 12) | ((info[11] as u32) << 4) | ((info[12] as u32) >> 4);
+    let channels = ((info[12] >> 1) & 0x07) as u16 + 1;
+    let total_samples = (((info[13] & 0x0f) as u64) << 32)
+        | u32::from_be_bytes([info[14], info[15], info[16], info[17]]) as u64;
+    if sample_rate == 0 || total_samples == 0 {
+        return None;
+    }
+    Some(AudioInfo {
+        channels,
+        duration_secs: total_samples as f64 / sample_rate as f64,
+    })
+}
+
+/// Skips the rest of a chunk; RIFF and IFF chunks are padded to an even size.
+fn skip_chunk(file: &mut File, remaining: i64, size: u32) -> Option<()> {
+    let pad = (size % 2) as i64;
+    file.seek(SeekFrom::Current(remaining.max(0) + pad)).ok()?;
+    Some(())
+}
+
+/// Converts the 80-bit IEEE 754 extended float AIFF uses for its sample rate.
+fn extended_to_f64(bytes: &[u8]) -> f64 {
+    let exponent = (((bytes[0] & 0x7f) as i32) << 8) | bytes[1] as i32;
+    let mantissa = u64::from_be_bytes([
+        bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7], bytes[8], bytes[9],
+    ]);
+    if exponent == 0 && mantissa == 0 {
+        return 0.0;
+    }
+    let value = mantissa as f64 * 2f64.powi(exponent - 16383 - 63);
+    if bytes[0] & 0x80 != 0 { -value } else { value }
+}
@@ -0,0 +1,31 @@
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Path of the shared Devalang user config, `~/.devalang/config.json`.
+pub fn config_path() -> Result<PathBuf, String> {
+    let home = crate::utils::fs::get_user_home()?;
+    Ok(home.join(".devalang").join("config.json"))
+}
+
+/// Reads a devapack setting from the `"devapack"` object of `~/.devalang/config.json`.
+///
+/// Returns `None` when the file, the section or the key is missing.
+///
+/// ### Parameters
+/// - `key`: The setting name, e.g. `devalang_version`
+///
+pub fn devapack_setting(key: &str) -> Option<String> {
+    let txt = std::fs::read_to_string(config_path().ok()?).ok()?;
+    let cfg: Value = txt.parse().ok()?;
+    cfg.get("devapack")?
+        .get(key)?
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Directory for values devapack caches between runs, `~/.devalang/cache`.
+pub fn cache_dir() -> Result<PathBuf, String> {
+    let home = crate::utils::fs::get_user_home()?;
+    Ok(home.join(".devalang").join("cache"))
+}
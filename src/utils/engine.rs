@@ -0,0 +1,112 @@
+use crate::utils::fs as ufs;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// `[plugin.engine]` / `[bank.engine]`: the Devalang versions an addon is compatible with.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct EngineSection {
+    /// Version requirement, e.g. `>=0.1, <0.3`
+    #[serde(default)]
+    pub devalang: Option<String>,
+    /// `devalang` crate version the plugin was last built against, filled by `plugin build`
+    #[serde(default)]
+    pub devalang_resolved: Option<String>,
+}
+
+/// Parses a devalang version requirement such as `>=0.1, <0.3`.
+///
+/// ### Parameters
+/// - `requirement`: The requirement string from the manifest
+///
+pub fn parse_requirement(requirement: &str) -> Result<VersionReq, String> {
+    VersionReq::parse(requirement.trim()).map_err(|e| {
+        format!(
+            "Invalid engine requirement 'devalang = \"{}\"': {}",
+            requirement, e
+        )
+    })
+}
+
+/// Reads and validates the `engine.devalang` requirement of an addon manifest.
+///
+/// ### Parameters
+/// - `manifest`: The bank.toml or plugin.toml content
+/// - `section`: The top-level section, `bank` or `plugin`
+///
+pub fn manifest_requirement(manifest: &str, section: &str) -> Result<Option<String>, String> {
+    let doc: toml::Value = toml::from_str(manifest).map_err(|e| format!("Invalid TOML: {}", e))?;
+    let requirement = doc
+        .get(section)
+        .and_then(|s| s.get("engine"))
+        .and_then(|e| e.get("devalang"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    if let Some(req) = &requirement {
+        parse_requirement(req)?;
+    }
+    Ok(requirement)
+}
+
+/// Finds the `devalang` version locked for a plugin crate.
+///
+/// Looks for the nearest Cargo.lock from `crate_dir` upwards, which covers standalone
+/// plugins and the shared `generated/plugins` workspace.
+///
+/// ### Parameters
+/// - `crate_dir`: The plugin crate directory
+///
+pub fn locked_devalang_version(crate_dir: &Path) -> Option<String> {
+    let lock_path = ufs::find_in_ancestors(crate_dir, "Cargo.lock")?;
+    let lock: toml::Value = toml::from_str(&std::fs::read_to_string(lock_path).ok()?).ok()?;
+    lock.get("package")?
+        .as_array()?
+        .iter()
+        .find(|p| p.get("name").and_then(|n| n.as_str()) == Some("devalang"))?
+        .get("version")?
+        .as_str()
+        .map(|v| v.to_string())
+}
+
+/// Completes a plugin's engine section with the version it was just built against.
+///
+/// A missing requirement defaults to `^<resolved>`; a declared one must accept the
+/// resolved version.
+///
+/// ### Parameters
+/// - `declared`: The engine section from plugin.toml, if any
+/// - `resolved`: The devalang version from Cargo.lock, if found
+///
+pub fn resolve_plugin_engine(
+    declared: Option<EngineSection>,
+    resolved: Option<String>,
+) -> Result<EngineSection, String> {
+    let mut engine = declared.unwrap_or_default();
+    if let Some(req) = &engine.devalang {
+        parse_requirement(req)?;
+    }
+    let Some(resolved) = resolved else {
+        return Ok(engine);
+    };
+
+    match &engine.devalang {
+        Some(req) => {
+            let version = Version::parse(&resolved).map_err(|e| {
+                format!(
+                    "Invalid devalang version '{}' in Cargo.lock: {}",
+                    resolved, e
+                )
+            })?;
+            if !parse_requirement(req)?.matches(&version) {
+                return Err(format!(
+                    "plugin.toml requires devalang {} but the plugin is built against {}; update [plugin.engine] or the devalang dependency",
+                    req, resolved
+                ));
+            }
+        }
+        None => engine.devalang = Some(format!("^{}", resolved)),
+    }
+    engine.devalang_resolved = Some(resolved);
+    Ok(engine)
+}
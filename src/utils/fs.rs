@@ -42,3 +42,29 @@ pub fn is_ignored_component(name: &str) -> bool {
         "node_modules" | ".git" | "target" | "dist" | "build" | "out"
     )
 }
+
+/// Finds `file_name` in `dir` or the closest of its parent directories.
+pub fn find_in_ancestors(dir: &Path, file_name: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(file_name))
+        .find(|p| p.is_file())
+}
+
+/// Lists the files of an addon directory as `(unix relative path, path)` pairs, sorted,
+/// skipping the directories `is_ignored_component` rejects.
+pub fn addon_files(addon_dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for f in walk_files(addon_dir)? {
+        let Some(rel) = path_relative_to(&f, addon_dir) else {
+            continue;
+        };
+        let skip = rel
+            .iter()
+            .any(|comp| comp.to_str().map(is_ignored_component).unwrap_or(false));
+        if !skip {
+            files.push((to_unix_string(&rel), f));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,121 @@
+use crate::utils::keyfile;
+use base64::{Engine as _, engine::general_purpose};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+/// Environment variable holding the signing key itself, base64-encoded.
+pub const KEY_ENV: &str = "DEVAPACK_SIGNING_KEY";
+/// Environment variable holding the path of the signing key, or `-` to read it from stdin.
+pub const KEY_FILE_ENV: &str = "DEVAPACK_SIGNING_KEY_FILE";
+/// Environment variable turning CI mode on; the conventional `CI` variable does too.
+pub const CI_ENV: &str = "DEVAPACK_CI";
+
+/// Where the signing key is read from.
+///
+/// Every command that signs loads the key through `KeySource::current`, so the
+/// environment decides which key signs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// `~/.devalang/keys/ed25519.key`, the only source devapack creates keys in
+    DefaultFile(PathBuf),
+    /// The base64 content of `DEVAPACK_SIGNING_KEY`
+    Env,
+    /// The file named by `DEVAPACK_SIGNING_KEY_FILE`
+    File(PathBuf),
+    /// Standard input, with `DEVAPACK_SIGNING_KEY_FILE=-`
+    Stdin,
+}
+
+impl KeySource {
+    /// The source selected by the environment, the default key file otherwise.
+    pub fn current() -> Result<Self, String> {
+        match (non_empty_env(KEY_ENV), non_empty_env(KEY_FILE_ENV)) {
+            (Some(_), Some(_)) => Err(format!(
+                "Both {} and {} are set; set only one",
+                KEY_ENV, KEY_FILE_ENV
+            )),
+            (Some(_), None) => Ok(Self::Env),
+            (None, Some(path)) if path == "-" => Ok(Self::Stdin),
+            (None, Some(path)) => Ok(Self::File(PathBuf::from(path))),
+            (None, None) => Ok(Self::DefaultFile(crate::utils::signing::key_path()?)),
+        }
+    }
+
+    /// Human-readable name of the source, for logs.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::DefaultFile(path) | Self::File(path) => path.display().to_string(),
+            Self::Env => format!("${}", KEY_ENV),
+            Self::Stdin => "stdin".to_string(),
+        }
+    }
+
+    pub fn is_default_file(&self) -> bool {
+        matches!(self, Self::DefaultFile(_))
+    }
+
+    /// Whether a key can be read from this source without creating one.
+    pub fn exists(&self) -> bool {
+        match self {
+            Self::DefaultFile(path) | Self::File(path) => path.exists(),
+            Self::Env | Self::Stdin => true,
+        }
+    }
+
+    /// Reads the key, in any format a key file may have, base64-encoded or not.
+    ///
+    /// Standard input is read once per run.
+    pub fn read(&self) -> Result<Zeroizing<Vec<u8>>, String> {
+        let raw = match self {
+            Self::DefaultFile(path) | Self::File(path) => Zeroizing::new(
+                std::fs::read(path).map_err(|e| format!("Failed to read key file: {}", e))?,
+            ),
+            Self::Env => Zeroizing::new(
+                non_empty_env(KEY_ENV)
+                    .ok_or_else(|| format!("{} is not set", KEY_ENV))?
+                    .into_bytes(),
+            ),
+            Self::Stdin => {
+                static STDIN_KEY: OnceLock<Result<Zeroizing<Vec<u8>>, String>> = OnceLock::new();
+                STDIN_KEY
+                    .get_or_init(|| {
+                        let mut buf = Zeroizing::new(Vec::new());
+                        std::io::stdin()
+                            .read_to_end(&mut buf)
+                            .map_err(|e| format!("Failed to read the key from stdin: {}", e))?;
+                        Ok(buf)
+                    })
+                    .clone()?
+            }
+        };
+        decode_key_material(&raw).map_err(|e| format!("{} ({})", e, self.describe()))
+    }
+}
+
+/// Whether devapack runs in CI, where it must never generate a signing key.
+pub fn ci_mode() -> bool {
+    [CI_ENV, "CI"].iter().any(|var| {
+        non_empty_env(var).is_some_and(|v| !matches!(v.to_lowercase().as_str(), "0" | "false"))
+    })
+}
+
+/// Accepts key bytes as stored on disk (raw or encrypted) or their base64 text.
+fn decode_key_material(raw: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    if raw.iter().all(u8::is_ascii_whitespace) {
+        return Err("Signing key is empty".to_string());
+    }
+    if keyfile::is_encrypted(raw) || raw.len() == 32 || raw.len() == 64 {
+        return Ok(Zeroizing::new(raw.to_vec()));
+    }
+    let text = String::from_utf8_lossy(raw);
+    general_purpose::STANDARD
+        .decode(text.trim())
+        .map(Zeroizing::new)
+        .map_err(|_| "Signing key is neither a key file nor a base64-encoded one".to_string())
+}
+
+fn non_empty_env(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|v| !v.is_empty())
+}
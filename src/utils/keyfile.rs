@@ -0,0 +1,252 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    XChaCha20Poly1305, XNonce,
+    aead::{Aead, KeyInit, Payload},
+};
+use zeroize::Zeroizing;
+
+/// First bytes of an encrypted key file.
+pub const MAGIC: &[u8; 7] = b"DEVAKEY";
+/// Current version of the encrypted key-file format.
+pub const FORMAT_VERSION: u8 = 1;
+/// Environment variable holding the key passphrase, for CI and other non-interactive runs.
+pub const PASSPHRASE_ENV: &str = "DEVAPACK_KEY_PASSPHRASE";
+
+const PUBLIC_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const SECRET_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+// magic | version | public key | m_cost, t_cost, p_cost (u32 LE) | salt | nonce
+const HEADER_LEN: usize = MAGIC.len() + 1 + PUBLIC_LEN + 12 + SALT_LEN + NONCE_LEN;
+const FILE_LEN: usize = HEADER_LEN + SECRET_LEN + TAG_LEN;
+// Refuse KDF settings that would take unreasonable memory (2 GiB) to open
+const MAX_M_COST_KIB: u32 = 2 * 1024 * 1024;
+
+/// Whether a key file uses the encrypted format, as opposed to raw key bytes.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+///
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Reads the public key stored in clear in an encrypted key file's header.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+///
+pub fn public_key(bytes: &[u8]) -> Result<[u8; 32], String> {
+    check_header(bytes)?;
+    let start = MAGIC.len() + 1;
+    let mut public = [0u8; PUBLIC_LEN];
+    public.copy_from_slice(&bytes[start..start + PUBLIC_LEN]);
+    Ok(public)
+}
+
+/// Encrypts an Ed25519 secret with a passphrase.
+///
+/// The passphrase is stretched with Argon2id into a XChaCha20-Poly1305 key. The whole
+/// header, public key included, is authenticated along with the secret.
+///
+/// ### Parameters
+/// - `secret`: The 32-byte Ed25519 secret
+/// - `public`: Its public key, kept readable so the key can be identified without the passphrase
+/// - `passphrase`: The passphrase
+///
+pub fn encrypt(secret: &[u8], public: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| format!("Random failed: {}", e))?;
+    getrandom::getrandom(&mut nonce).map_err(|e| format!("Random failed: {}", e))?;
+
+    let mut out = Vec::with_capacity(FILE_LEN);
+    out.extend_from_slice(MAGIC);
+    out.push(FORMAT_VERSION);
+    out.extend_from_slice(public);
+    out.extend_from_slice(&params.m_cost().to_le_bytes());
+    out.extend_from_slice(&params.t_cost().to_le_bytes());
+    out.extend_from_slice(&params.p_cost().to_le_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+
+    let cipher = cipher(passphrase, &salt, params)?;
+    let sealed = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: secret,
+                aad: &out,
+            },
+        )
+        .map_err(|_| "Failed to encrypt the signing key".to_string())?;
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Decrypts the Ed25519 secret of an encrypted key file.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+/// - `passphrase`: The passphrase it was encrypted with
+///
+pub fn decrypt(bytes: &[u8], passphrase: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    check_header(bytes)?;
+    let mut offset = MAGIC.len() + 1 + PUBLIC_LEN;
+    let mut read_u32 = || {
+        let v = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        v
+    };
+    let (m_cost, t_cost, p_cost) = (read_u32(), read_u32(), read_u32());
+    if m_cost > MAX_M_COST_KIB {
+        return Err(format!(
+            "Key file asks for {} MiB of memory to unlock; refusing",
+            m_cost / 1024
+        ));
+    }
+    let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| format!("Invalid key file KDF parameters: {}", e))?;
+    let salt = &bytes[HEADER_LEN - NONCE_LEN - SALT_LEN..HEADER_LEN - NONCE_LEN];
+    let nonce = &bytes[HEADER_LEN - NONCE_LEN..HEADER_LEN];
+
+    let secret = cipher(passphrase, salt, params)?
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: &bytes[HEADER_LEN..],
+                aad: &bytes[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| {
+            "Wrong passphrase for the signing key, or the key file is corrupted".to_string()
+        })?;
+    Ok(Zeroizing::new(secret))
+}
+
+/// Checks the magic, version and length of an encrypted key file.
+fn check_header(bytes: &[u8]) -> Result<(), String> {
+    if !is_encrypted(bytes) {
+        return Err("Not an encrypted devapack key file".to_string());
+    }
+    let version = bytes.get(MAGIC.len()).copied().unwrap_or(0);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported key file format version {} (this devapack reads version {})",
+            version, FORMAT_VERSION
+        ));
+    }
+    if bytes.len() != FILE_LEN {
+        return Err(format!(
+            "Encrypted key file is {} bytes; expected {}",
+            bytes.len(),
+            FILE_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Derives the AEAD key from the passphrase with Argon2id.
+fn cipher(passphrase: &str, salt: &[u8], params: Params) -> Result<XChaCha20Poly1305, String> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("Failed to derive the key encryption key: {}", e))?;
+    Ok(XChaCha20Poly1305::new(key.as_ref().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: [u8; 32] = [7u8; 32];
+    const PUBLIC: [u8; 32] = [9u8; 32];
+
+    /// Offset of the public key in the header.
+    const PUBLIC_AT: usize = MAGIC.len() + 1;
+
+    #[test]
+    fn round_trips() {
+        let file = encrypt(&SECRET, &PUBLIC, "correct horse").unwrap();
+        assert_eq!(file.len(), FILE_LEN);
+        assert!(is_encrypted(&file));
+        assert_eq!(public_key(&file).unwrap(), PUBLIC);
+        assert_eq!(decrypt(&file, "correct horse").unwrap().as_slice(), SECRET);
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase_and_tampering() {
+        let file = encrypt(&SECRET, &PUBLIC, "correct horse").unwrap();
+        assert!(
+            decrypt(&file, "battery staple")
+                .unwrap_err()
+                .contains("Wrong passphrase")
+        );
+
+        // the public key is in clear but authenticated with the secret
+        let mut swapped = file.clone();
+        swapped[PUBLIC_AT] ^= 1;
+        assert!(decrypt(&swapped, "correct horse").is_err());
+
+        let mut sealed = file.clone();
+        sealed[HEADER_LEN] ^= 1;
+        assert!(decrypt(&sealed, "correct horse").is_err());
+
+        let mut tag = file;
+        tag[FILE_LEN - 1] ^= 1;
+        assert!(decrypt(&tag, "correct horse").is_err());
+    }
+
+    /// A well-formed file with arbitrary content, for the checks that run before the KDF.
+    fn blank_file() -> Vec<u8> {
+        let mut file = vec![0u8; FILE_LEN];
+        file[..MAGIC.len()].copy_from_slice(MAGIC);
+        file[MAGIC.len()] = FORMAT_VERSION;
+        file
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        let file = blank_file();
+        for len in [MAGIC.len() + 1, HEADER_LEN, FILE_LEN - 1] {
+            let err = decrypt(&file[..len], "x").unwrap_err();
+            assert!(err.contains("expected"), "{}", err);
+            assert!(public_key(&file[..len]).is_err());
+        }
+        let mut longer = file;
+        longer.push(0);
+        assert!(decrypt(&longer, "x").unwrap_err().contains("expected"));
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_version() {
+        let mut magic = blank_file();
+        magic[0] = b'X';
+        assert!(!is_encrypted(&magic));
+        assert!(
+            decrypt(&magic, "x")
+                .unwrap_err()
+                .contains("Not an encrypted")
+        );
+        assert!(decrypt(MAGIC, "x").unwrap_err().contains("Unsupported"));
+
+        let mut version = blank_file();
+        version[MAGIC.len()] = FORMAT_VERSION + 1;
+        let err = decrypt(&version, "x").unwrap_err();
+        assert!(
+            err.contains("Unsupported key file format version"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn refuses_excessive_memory_cost() {
+        let mut file = blank_file();
+        let m_cost_at = PUBLIC_AT + PUBLIC_LEN;
+        file[m_cost_at..m_cost_at + 4].copy_from_slice(&(MAX_M_COST_KIB + 1).to_le_bytes());
+        assert!(decrypt(&file, "x").unwrap_err().contains("refusing"));
+    }
+}
@@ -1,8 +1,46 @@
 #[cfg(feature = "cli")]
 use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
+use std::cell::RefCell;
 #[cfg(feature = "cli")]
 use std::fmt::Write;
 
+thread_local! {
+    /// Output of the current thread while `capture` runs, instead of stdout.
+    static CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns what it logged on this thread instead of printing it, so
+/// concurrent builds can print their output as one block each.
+///
+/// ### Parameters
+/// - `f`: The work whose output is captured
+///
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let previous = CAPTURE.with(|c| c.replace(Some(String::new())));
+    let value = f();
+    let output = CAPTURE.with(|c| c.replace(previous)).unwrap_or_default();
+    (value, output)
+}
+
+/// Prints a line to stdout, or appends it to the output captured on this thread.
+///
+/// ### Parameters
+/// - `line`: The line, without its trailing newline
+///
+pub fn print_line(line: &str) {
+    let captured = CAPTURE.with(|c| match c.borrow_mut().as_mut() {
+        Some(buf) => {
+            buf.push_str(line);
+            buf.push('\n');
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        println!("{}", line);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LogLevel {
     Success,
@@ -39,12 +77,12 @@ impl Logger {
     #[cfg(feature = "cli")]
     pub fn log_message(&self, level: LogLevel, message: &str) {
         let formatted_status = self.format_status(level);
-        println!(
+        print_line(&format!(
             "⚒️  {} {} {}",
             self.language_signature(),
             formatted_status,
             message
-        );
+        ));
     }
 
     #[cfg(not(feature = "cli"))]
@@ -55,14 +93,14 @@ impl Logger {
     #[cfg(feature = "cli")]
     pub fn log_message_with_trace(&self, level: LogLevel, message: &str, trace: Vec<&str>) {
         let formatted_status = self.format_status(level);
-        println!(
+        print_line(&format!(
             "⚒️  {} {} {}",
             self.language_signature(),
             formatted_status,
             message
-        );
+        ));
         for t in trace {
-            println!("     ↳ {}", t);
+            print_line(&format!("     ↳ {}", t));
         }
     }
 
@@ -75,13 +113,13 @@ impl Logger {
     #[allow(dead_code)]
     pub fn log_error_with_stacktrace(&self, message: &str, stacktrace: &str) {
         let formatted_status = self.format_status(LogLevel::Error);
-        println!(
+        print_line(&format!(
             "⚒️  {} {} {}",
             self.language_signature(),
             formatted_status,
             message
-        );
-        println!("     ↳ {}", stacktrace);
+        ));
+        print_line(&format!("     ↳ {}", stacktrace));
     }
 
     #[cfg(not(feature = "cli"))]
@@ -0,0 +1,355 @@
+use crate::utils::signing;
+use base64::{Engine as _, engine::general_purpose};
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+
+/// Algorithm tag of public keys and of signatures over the file itself.
+const ALG_LEGACY: &[u8; 2] = b"Ed";
+/// Algorithm tag of signatures over the BLAKE2b-512 of the file (minisign's default).
+const ALG_PREHASHED: &[u8; 2] = b"ED";
+const UNTRUSTED_PREFIX: &str = "untrusted comment:";
+const TRUSTED_PREFIX: &str = "trusted comment: ";
+
+/// A parsed minisign signature file.
+#[derive(Debug, Clone)]
+pub struct MinisignSignature {
+    /// Whether the signature covers the BLAKE2b-512 of the file rather than the file
+    pub prehashed: bool,
+    pub key_id: [u8; 8],
+    pub signature: [u8; 64],
+    /// Signed text, by default the signing time and the file name
+    pub trusted_comment: String,
+    /// Signature of `signature` followed by `trusted_comment`
+    pub global_signature: [u8; 64],
+}
+
+/// Minisign key id of an Ed25519 public key.
+///
+/// The bytes are those of the devapack fingerprint, so both identify the same key.
+///
+/// ### Parameters
+/// - `public`: The Ed25519 public key
+///
+pub fn key_id(public: &PublicKey) -> [u8; 8] {
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&Sha256::digest(public.as_bytes())[..8]);
+    id
+}
+
+/// A key id as minisign prints it: the bytes read as a little-endian integer, in upper hex.
+///
+/// ### Parameters
+/// - `id`: The key id
+///
+pub fn key_id_hex(id: &[u8; 8]) -> String {
+    format!("{:016X}", u64::from_le_bytes(*id))
+}
+
+/// Encodes a public key as a minisign public key file (`minisign.pub`).
+///
+/// ### Parameters
+/// - `public`: The Ed25519 public key
+///
+pub fn encode_public_key(public: &PublicKey) -> String {
+    let id = key_id(public);
+    let mut raw = Vec::with_capacity(42);
+    raw.extend_from_slice(ALG_LEGACY);
+    raw.extend_from_slice(&id);
+    raw.extend_from_slice(public.as_bytes());
+    format!(
+        "{} minisign public key {}\n{}\n",
+        UNTRUSTED_PREFIX,
+        key_id_hex(&id),
+        general_purpose::STANDARD.encode(raw)
+    )
+}
+
+/// Parses a minisign public key, either a whole `minisign.pub` file or its base64 line.
+///
+/// ### Parameters
+/// - `text`: The public key
+///
+pub fn decode_public_key(text: &str) -> Result<([u8; 8], PublicKey), String> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with(UNTRUSTED_PREFIX))
+        .ok_or("Empty minisign public key")?;
+    let raw = general_purpose::STANDARD
+        .decode(line)
+        .map_err(|e| format!("Minisign public key is not valid base64: {}", e))?;
+    if raw.len() != 42 || &raw[..2] != ALG_LEGACY {
+        return Err("Not a minisign Ed25519 public key".to_string());
+    }
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&raw[2..10]);
+    let public = PublicKey::from_bytes(&raw[10..])
+        .map_err(|e| format!("Invalid Ed25519 public key: {}", e))?;
+    Ok((id, public))
+}
+
+/// Whether a signature file uses the minisign format.
+///
+/// ### Parameters
+/// - `text`: The signature file content
+///
+pub fn is_signature(text: &str) -> bool {
+    text.trim_start().starts_with(UNTRUSTED_PREFIX)
+}
+
+/// Path of the minisign signature of a file: `<file>.minisig`, where minisign looks for it.
+///
+/// ### Parameters
+/// - `file`: The signed file
+///
+pub fn signature_path(file: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.minisig", file.display()))
+}
+
+/// Signs data in minisign's prehashed mode and encodes the signature file.
+///
+/// ### Parameters
+/// - `kp`: The signing keypair
+/// - `data`: The signed file content
+/// - `trusted_comment`: Text covered by the global signature, on a single line
+///
+pub fn sign(kp: &Keypair, data: &[u8], trusted_comment: &str) -> String {
+    let id = key_id(&kp.public);
+    let signature = kp.sign(&Blake2b512::digest(data)).to_bytes();
+    let mut global = signature.to_vec();
+    global.extend_from_slice(trusted_comment.as_bytes());
+    let global_signature = kp.sign(&global).to_bytes();
+
+    let mut raw = Vec::with_capacity(74);
+    raw.extend_from_slice(ALG_PREHASHED);
+    raw.extend_from_slice(&id);
+    raw.extend_from_slice(&signature);
+    format!(
+        "{} signature from devapack secret key {}\n{}\n{}{}\n{}\n",
+        UNTRUSTED_PREFIX,
+        key_id_hex(&id),
+        general_purpose::STANDARD.encode(raw),
+        TRUSTED_PREFIX,
+        trusted_comment,
+        general_purpose::STANDARD.encode(global_signature)
+    )
+}
+
+/// Signs a file with the local key and writes `<file>.minisig`, returning the key id.
+///
+/// The trusted comment records the signing time and the file name, as minisign does.
+///
+/// ### Parameters
+/// - `file`: The file to sign, usually a build archive
+///
+pub fn sign_file(file: &Path) -> Result<String, String> {
+    let bytes =
+        std::fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let kp = signing::load_keypair()?;
+    let name = file
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let trusted_comment = format!(
+        "timestamp:{}\tfile:{}\thashed",
+        chrono::Utc::now().timestamp(),
+        name
+    );
+    let sig_path = signature_path(file);
+    std::fs::write(&sig_path, sign(&kp, &bytes, &trusted_comment))
+        .map_err(|e| format!("Failed to write {}: {}", sig_path.display(), e))?;
+    Ok(key_id_hex(&key_id(&kp.public)))
+}
+
+/// Parses a minisign signature file.
+///
+/// ### Parameters
+/// - `text`: The signature file content
+///
+pub fn parse_signature(text: &str) -> Result<MinisignSignature, String> {
+    let mut lines = text.lines().map(|l| l.trim_end_matches('\r'));
+    let invalid = |what: &str| format!("Invalid minisign signature: {}", what);
+    if !lines
+        .next()
+        .is_some_and(|l| l.starts_with(UNTRUSTED_PREFIX))
+    {
+        return Err(invalid("missing untrusted comment"));
+    }
+    let raw = lines
+        .next()
+        .and_then(|l| general_purpose::STANDARD.decode(l.trim()).ok())
+        .filter(|raw| raw.len() == 74)
+        .ok_or_else(|| invalid("bad signature line"))?;
+    let prehashed = match &raw[..2] {
+        a if a == ALG_PREHASHED => true,
+        a if a == ALG_LEGACY => false,
+        _ => return Err(invalid("unsupported algorithm")),
+    };
+    let trusted_comment = lines
+        .next()
+        .and_then(|l| l.strip_prefix(TRUSTED_PREFIX))
+        .ok_or_else(|| invalid("missing trusted comment"))?
+        .to_string();
+    let global = lines
+        .next()
+        .and_then(|l| general_purpose::STANDARD.decode(l.trim()).ok())
+        .filter(|g| g.len() == 64)
+        .ok_or_else(|| invalid("bad global signature line"))?;
+
+    let mut sig = MinisignSignature {
+        prehashed,
+        key_id: [0u8; 8],
+        signature: [0u8; 64],
+        trusted_comment,
+        global_signature: [0u8; 64],
+    };
+    sig.key_id.copy_from_slice(&raw[2..10]);
+    sig.signature.copy_from_slice(&raw[10..]);
+    sig.global_signature.copy_from_slice(&global);
+    Ok(sig)
+}
+
+/// Checks a minisign signature and its trusted comment against a file's content.
+///
+/// The key id is not compared: it only hints at the key, and minisign's own keys use
+/// random ids.
+///
+/// ### Parameters
+/// - `sig`: The parsed signature
+/// - `public`: The signer's public key
+/// - `data`: The signed file content
+///
+pub fn verify(sig: &MinisignSignature, public: &PublicKey, data: &[u8]) -> Result<bool, String> {
+    let signature = Signature::from_bytes(&sig.signature)
+        .map_err(|e| format!("Invalid minisign signature: {}", e))?;
+    let global_signature = Signature::from_bytes(&sig.global_signature)
+        .map_err(|e| format!("Invalid minisign global signature: {}", e))?;
+    let signed_ok = if sig.prehashed {
+        public.verify_strict(&Blake2b512::digest(data), &signature)
+    } else {
+        public.verify_strict(data, &signature)
+    }
+    .is_ok();
+    let mut global = sig.signature.to_vec();
+    global.extend_from_slice(sig.trusted_comment.as_bytes());
+    Ok(signed_ok && public.verify_strict(&global, &global_signature).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SecretKey;
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[3u8; 32]).unwrap();
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    /// A legacy (`Ed`) signature file: the data itself is signed, not its BLAKE2b-512.
+    fn sign_legacy(kp: &Keypair, data: &[u8], trusted_comment: &str) -> String {
+        let signature = kp.sign(data).to_bytes();
+        let mut global = signature.to_vec();
+        global.extend_from_slice(trusted_comment.as_bytes());
+        let mut raw = ALG_LEGACY.to_vec();
+        raw.extend_from_slice(&key_id(&kp.public));
+        raw.extend_from_slice(&signature);
+        format!(
+            "{} legacy\n{}\n{}{}\n{}\n",
+            UNTRUSTED_PREFIX,
+            general_purpose::STANDARD.encode(raw),
+            TRUSTED_PREFIX,
+            trusted_comment,
+            general_purpose::STANDARD.encode(kp.sign(&global).to_bytes())
+        )
+    }
+
+    #[test]
+    fn prehashed_signature_round_trips() {
+        let kp = keypair();
+        let text = sign(&kp, b"archive bytes", "timestamp:0\tfile:a.tar.gz\thashed");
+        assert!(is_signature(&text));
+        let sig = parse_signature(&text).unwrap();
+        assert!(sig.prehashed);
+        assert_eq!(sig.key_id, key_id(&kp.public));
+        assert_eq!(sig.trusted_comment, "timestamp:0\tfile:a.tar.gz\thashed");
+        assert!(verify(&sig, &kp.public, b"archive bytes").unwrap());
+        assert!(!verify(&sig, &kp.public, b"other bytes").unwrap());
+    }
+
+    #[test]
+    fn legacy_signature_round_trips() {
+        let kp = keypair();
+        let sig = parse_signature(&sign_legacy(&kp, b"archive bytes", "legacy")).unwrap();
+        assert!(!sig.prehashed);
+        assert!(verify(&sig, &kp.public, b"archive bytes").unwrap());
+        assert!(!verify(&sig, &kp.public, b"other bytes").unwrap());
+    }
+
+    #[test]
+    fn edited_trusted_comment_fails() {
+        let kp = keypair();
+        for text in [
+            sign(&kp, b"data", "file:a.tar.gz"),
+            sign_legacy(&kp, b"data", "file:a.tar.gz"),
+        ] {
+            let edited = text.replace("file:a.tar.gz", "file:b.tar.gz");
+            let sig = parse_signature(&edited).unwrap();
+            assert!(!verify(&sig, &kp.public, b"data").unwrap());
+        }
+    }
+
+    #[test]
+    fn rejects_another_key() {
+        let kp = keypair();
+        let other = PublicKey::from(&SecretKey::from_bytes(&[4u8; 32]).unwrap());
+        let sig = parse_signature(&sign(&kp, b"data", "c")).unwrap();
+        assert!(!verify(&sig, &other, b"data").unwrap());
+    }
+
+    #[test]
+    fn public_key_file_matches_signatures() {
+        let kp = keypair();
+        let file = encode_public_key(&kp.public);
+        let (id, public) = decode_public_key(&file).unwrap();
+        assert_eq!(public, kp.public);
+        assert_eq!(id, key_id(&kp.public));
+        // minisign shows the id in the untrusted comment and matches it against signatures
+        assert!(file.lines().next().unwrap().ends_with(&key_id_hex(&id)));
+        let sig = parse_signature(&sign(&kp, b"data", "c")).unwrap();
+        assert_eq!(sig.key_id, id);
+        // the base64 line alone decodes the same
+        assert_eq!(
+            decode_public_key(file.lines().nth(1).unwrap()).unwrap().0,
+            id
+        );
+    }
+
+    /// Signed by another minisign implementation, with a random key id as minisign uses.
+    const FOREIGN_PUBLIC_KEY: &str = "untrusted comment: minisign public key X\nRWTc7ygo3hfZvzrgy+DkCBzUUKjzVx7yoRTeB6IbmYYKCkc82uXzg9hf\n";
+    const FOREIGN_SIGNATURE: &str = "untrusted comment: sig\nRUTc7ygo3hfZv4F3ZWgeTPHK2zdYCCXjreX9eNvc5Ui4w+KiJpFvY4ArK1eT1IU4HNECCL2stogXMfGOTRb/GV9sl1NbsNg1tgM=\ntrusted comment: timestamp:1\tfile:x\nqb7PtUVaof4e/USIk5UcGFl+SexV9YGIfS/dJDgqq1BIQNb+Flhlj0Op1qKZNjVhA/WbAL8JK+o4722tdZXpBA==\n";
+
+    #[test]
+    fn verifies_foreign_signature() {
+        let (id, public) = decode_public_key(FOREIGN_PUBLIC_KEY).unwrap();
+        let sig = parse_signature(FOREIGN_SIGNATURE).unwrap();
+        assert!(sig.prehashed);
+        assert_eq!(sig.key_id, id);
+        assert_ne!(id, key_id(&public));
+        assert_eq!(sig.trusted_comment, "timestamp:1\tfile:x");
+        assert!(verify(&sig, &public, b"devapack interop\n").unwrap());
+        assert!(!verify(&sig, &public, b"devapack interop").unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        assert!(parse_signature("").is_err());
+        assert!(parse_signature("untrusted comment: x\nnot base64\n").is_err());
+        let kp = keypair();
+        let text = sign(&kp, b"data", "c");
+        let without_global: String = text.lines().take(3).map(|l| format!("{}\n", l)).collect();
+        assert!(parse_signature(&without_global).is_err());
+    }
+}
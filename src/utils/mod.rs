@@ -1,9 +1,16 @@
 pub mod api;
+pub mod audio;
 pub mod auth;
+pub mod config;
+pub mod engine;
 pub mod fs;
 pub mod kebab_case;
+pub mod key_source;
+pub mod keyfile;
 pub mod logger;
+pub mod minisign;
 pub mod path;
+pub mod prompt;
 pub mod semver;
 pub mod signature;
 pub mod signing;
@@ -0,0 +1,64 @@
+use std::io::IsTerminal;
+
+/// Whether prompts can be shown, i.e. stdin is a terminal.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Returns the value given on the command line, otherwise asks for it.
+///
+/// Without a terminal the default is used, or an error names the missing flag when
+/// the value is required.
+///
+/// ### Parameters
+/// - `value`: The flag value, if given
+/// - `flag`: The flag name without dashes, used in error messages
+/// - `message`: The prompt shown to the user
+/// - `default`: The prompt default, also used without a terminal when not required
+/// - `required`: Whether a non-interactive run must provide the flag
+///
+pub fn text(
+    value: Option<String>,
+    flag: &str,
+    message: &str,
+    default: &str,
+    required: bool,
+) -> Result<String, String> {
+    if let Some(v) = value {
+        return Ok(v);
+    }
+    if !is_interactive() {
+        if required {
+            return Err(format!(
+                "Missing --{}: stdin is not a terminal, so it cannot be prompted for",
+                flag
+            ));
+        }
+        return Ok(default.to_string());
+    }
+    inquire::Text::new(message)
+        .with_default(default)
+        .prompt()
+        .map_err(|e| format!("Failed to prompt for {}: {}", flag.replace('-', " "), e))
+}
+
+/// Asks for confirmation unless `--yes` was given.
+///
+/// ### Parameters
+/// - `yes`: Whether `--yes` was given
+/// - `message`: The confirmation question
+///
+pub fn confirm(yes: bool, message: &str) -> Result<bool, String> {
+    if yes {
+        return Ok(true);
+    }
+    if !is_interactive() {
+        return Err(
+            "Confirmation needed but stdin is not a terminal; pass --yes to proceed".to_string(),
+        );
+    }
+    inquire::Confirm::new(message)
+        .with_default(true)
+        .prompt()
+        .map_err(|e| format!("Failed to prompt for confirmation: {}", e))
+}
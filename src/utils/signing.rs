@@ -1,52 +1,438 @@
+use crate::utils::{
+    key_source::{self, KeySource},
+    keyfile,
+    logger::{LogLevel, Logger},
+    minisign, prompt,
+};
 use base64::{Engine as _, engine::general_purpose};
 use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer};
-use std::path::PathBuf;
+use inquire::PasswordDisplayMode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use zeroize::Zeroizing;
 
 pub fn key_path() -> Result<PathBuf, String> {
     let home = crate::utils::fs::get_user_home()?;
     Ok(home.join(".devalang").join("keys").join("ed25519.key"))
 }
 
-pub fn load_key_bytes() -> Result<Vec<u8>, String> {
-    let kp = key_path()?;
-    let bytes = std::fs::read(&kp).map_err(|e| format!("Failed to read key file: {}", e))?;
-    Ok(bytes)
+/// Reads the signing key from its source: the environment, stdin, or the key file.
+pub fn load_key_bytes() -> Result<Zeroizing<Vec<u8>>, String> {
+    KeySource::current()?.read()
 }
 
-pub fn ensure_keypair() -> Result<(), String> {
+/// Creates the local key if it is missing, encrypted with a new passphrase, and returns
+/// whether it did.
+///
+/// A key from the environment or stdin is checked instead. In CI mode a missing key is an
+/// error, so nothing gets signed with a throwaway key.
+pub fn ensure_keypair() -> Result<bool, String> {
+    let source = KeySource::current()?;
+    if !source.is_default_file() {
+        source.read()?;
+        return Ok(false);
+    }
     let keypth = key_path()?;
     if keypth.exists() {
-        return Ok(());
-    }
-    std::fs::create_dir_all(keypth.parent().unwrap())
-        .map_err(|e| format!("Failed to create keys dir: {}", e))?;
-    // generate random seed
-    let mut seed = [0u8; 32];
-    getrandom::getrandom(&mut seed).map_err(|e| format!("Random failed: {}", e))?;
-    let sk = SecretKey::from_bytes(&seed).map_err(|e| format!("SK derive failed: {}", e))?;
+        return Ok(false);
+    }
+    if key_source::ci_mode() {
+        return Err(format!(
+            "No signing key at {} and CI mode never generates one; set {} or {}",
+            keypth.display(),
+            key_source::KEY_ENV,
+            key_source::KEY_FILE_ENV
+        ));
+    }
+    let passphrase = new_passphrase()?;
+    write_key(&keypth, &generate_keypair()?, Some(&passphrase))?;
+    Ok(true)
+}
+
+/// Generates a new random Ed25519 keypair.
+pub fn generate_keypair() -> Result<Keypair, String> {
+    let mut seed = Zeroizing::new([0u8; 32]);
+    getrandom::getrandom(seed.as_mut()).map_err(|e| format!("Random failed: {}", e))?;
+    let sk =
+        SecretKey::from_bytes(seed.as_ref()).map_err(|e| format!("SK derive failed: {}", e))?;
     let public = PublicKey::from(&sk);
-    let kp_pair = Keypair { secret: sk, public };
-    std::fs::write(&keypth, kp_pair.to_bytes())
+    Ok(Keypair { secret: sk, public })
+}
+
+/// Writes a keypair, encrypted when a passphrase is given, readable by the current user only.
+///
+/// ### Parameters
+/// - `path`: The key file
+/// - `kp`: The keypair
+/// - `passphrase`: The passphrase to encrypt with, or `None` for the raw 64-byte format
+///
+pub fn write_key(path: &Path, kp: &Keypair, passphrase: Option<&str>) -> Result<(), String> {
+    let bytes = match passphrase {
+        Some(p) => Zeroizing::new(keyfile::encrypt(
+            kp.secret.as_bytes(),
+            kp.public.as_bytes(),
+            p,
+        )?),
+        None => Zeroizing::new(kp.to_bytes().to_vec()),
+    };
+    write_private_file(path, &bytes)
+}
+
+/// Writes a file only the current user can read (0600 on Unix).
+///
+/// The content goes to a temporary file created with those permissions and is then renamed,
+/// so the key is never readable by others, nor left half-written.
+///
+/// ### Parameters
+/// - `path`: The file to write
+/// - `bytes`: Its content
+///
+pub fn write_private_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create keys dir: {}", e))?;
+    }
+    let tmp = path.with_extension("tmp");
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| format!("Failed to create {}: {}", tmp.display(), e))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
         .map_err(|e| format!("Failed to write key file: {}", e))?;
+    std::fs::rename(&tmp, path)
+        .map_err(|e| format!("Failed to move key file into {}: {}", path.display(), e))
+}
+
+/// Parses an unencrypted key in either format devapack accepts: a 64-byte keypair or a
+/// 32-byte secret.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+///
+pub fn keypair_from_bytes(bytes: &[u8]) -> Result<Keypair, String> {
+    match bytes.len() {
+        64 => {
+            let kp = Keypair::from_bytes(bytes).map_err(|e| format!("Invalid keypair: {}", e))?;
+            if PublicKey::from(&kp.secret) != kp.public {
+                return Err("Invalid keypair: the public half does not match the secret".into());
+            }
+            Ok(kp)
+        }
+        32 => {
+            let sk = SecretKey::from_bytes(bytes).map_err(|e| format!("Invalid secret: {}", e))?;
+            let public = PublicKey::from(&sk);
+            Ok(Keypair { secret: sk, public })
+        }
+        n => Err(format!(
+            "Unsupported key length {}; expected a 32-byte secret or 64-byte keypair",
+            n
+        )),
+    }
+}
+
+/// Public key and decrypted secret of an unlocked key file.
+type UnlockedKey = ([u8; 32], Zeroizing<Vec<u8>>);
+
+/// Secret of the local key once unlocked, so one run asks for the passphrase at most once.
+static UNLOCKED: Mutex<Option<UnlockedKey>> = Mutex::new(None);
+
+/// Parses a key file in any format, decrypting it if needed.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+///
+pub fn keypair_from_file_bytes(bytes: &[u8]) -> Result<Keypair, String> {
+    if !keyfile::is_encrypted(bytes) {
+        return keypair_from_bytes(bytes);
+    }
+    let public = keyfile::public_key(bytes)?;
+    // Held while prompting, so parallel builds wait for the first one to unlock the key
+    let mut unlocked = UNLOCKED.lock().unwrap_or_else(|e| e.into_inner());
+    let secret = match unlocked.as_ref() {
+        Some((p, secret)) if *p == public => secret.clone(),
+        _ => {
+            let secret = keyfile::decrypt(bytes, &unlock_passphrase()?)?;
+            *unlocked = Some((public, secret.clone()));
+            secret
+        }
+    };
+    let kp = keypair_from_bytes(&secret)?;
+    if kp.public.as_bytes() != &public {
+        return Err("Encrypted key file's public key does not match its secret".to_string());
+    }
+    Ok(kp)
+}
+
+/// Loads the local signing key, asking for its passphrase when it is encrypted.
+pub fn load_keypair() -> Result<Keypair, String> {
+    let source = KeySource::current()?;
+    let bytes = source.read()?;
+    // Only a key kept on disk is worth encrypting; CI hands keys over in its own secrets
+    if source.is_default_file() && !keyfile::is_encrypted(&bytes) {
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| {
+            Logger::new().log_message(
+                LogLevel::Warning,
+                "The signing key is stored unencrypted; run 'devapack key passphrase' to encrypt it",
+            );
+        });
+    }
+    keypair_from_file_bytes(&bytes)
+}
+
+/// Asks for the key passphrase now, if the local key is encrypted and still locked.
+///
+/// Call before starting spinners, which would garble the prompt.
+pub fn unlock() -> Result<(), String> {
+    if KeySource::current()?.exists() {
+        load_keypair()?;
+    }
     Ok(())
 }
 
-pub fn sign_bytes(bytes: &[u8]) -> Result<(String, String), String> {
-    let key_bytes = load_key_bytes()?;
-    if key_bytes.len() == 64 {
-        let kp = Keypair::from_bytes(&key_bytes).map_err(|e| format!("Invalid keypair: {}", e))?;
-        let sig: Signature = kp.sign(bytes);
-        let sig_b64 = general_purpose::STANDARD.encode(sig.to_bytes());
-        let pub_b64 = general_purpose::STANDARD.encode(kp.public.to_bytes());
-        return Ok((sig_b64, pub_b64));
-    } else if key_bytes.len() == 32 {
-        let sk = SecretKey::from_bytes(&key_bytes).map_err(|e| format!("Invalid secret: {}", e))?;
-        let public = PublicKey::from(&sk);
-        let kp = Keypair { secret: sk, public };
-        let sig: Signature = kp.sign(bytes);
-        let sig_b64 = general_purpose::STANDARD.encode(sig.to_bytes());
-        let pub_b64 = general_purpose::STANDARD.encode(kp.public.to_bytes());
-        return Ok((sig_b64, pub_b64));
-    }
-    Err("Unsupported key length".to_string())
+/// Public key of a key file in any format, read without its passphrase.
+///
+/// ### Parameters
+/// - `bytes`: The key file content
+///
+pub fn public_key_from_file_bytes(bytes: &[u8]) -> Result<PublicKey, String> {
+    if keyfile::is_encrypted(bytes) {
+        PublicKey::from_bytes(&keyfile::public_key(bytes)?)
+            .map_err(|e| format!("Invalid public key in key file: {}", e))
+    } else {
+        Ok(keypair_from_bytes(bytes)?.public)
+    }
+}
+
+/// Public key of the local signing key, read without its passphrase.
+pub fn local_public_key() -> Result<PublicKey, String> {
+    public_key_from_file_bytes(&load_key_bytes()?)
+}
+
+/// The passphrase of the local key: `DEVAPACK_KEY_PASSPHRASE`, otherwise a prompt.
+fn unlock_passphrase() -> Result<Zeroizing<String>, String> {
+    if let Some(p) = env_passphrase() {
+        return Ok(p);
+    }
+    if !prompt::is_interactive() {
+        return Err(format!(
+            "The signing key is encrypted; set {} or run in a terminal",
+            keyfile::PASSPHRASE_ENV
+        ));
+    }
+    inquire::Password::new("Signing key passphrase:")
+        .without_confirmation()
+        .with_display_mode(PasswordDisplayMode::Hidden)
+        .prompt()
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to prompt for the key passphrase: {}", e))
+}
+
+/// A passphrase for a new key: `DEVAPACK_KEY_PASSPHRASE`, otherwise a prompt with confirmation.
+pub fn new_passphrase() -> Result<Zeroizing<String>, String> {
+    if let Some(p) = env_passphrase() {
+        return Ok(p);
+    }
+    if !prompt::is_interactive() {
+        return Err(format!(
+            "Set {} to encrypt the new signing key, or pass --no-passphrase to store it unencrypted",
+            keyfile::PASSPHRASE_ENV
+        ));
+    }
+    let passphrase = inquire::Password::new("New signing key passphrase:")
+        .with_custom_confirmation_message("Confirm the passphrase:")
+        .with_custom_confirmation_error_message("The passphrases don't match.")
+        .with_display_mode(PasswordDisplayMode::Hidden)
+        .prompt()
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to prompt for the key passphrase: {}", e))?;
+    if passphrase.is_empty() {
+        return Err(
+            "The passphrase cannot be empty; pass --no-passphrase to store the key unencrypted"
+                .to_string(),
+        );
+    }
+    Ok(passphrase)
+}
+
+fn env_passphrase() -> Option<Zeroizing<String>> {
+    std::env::var(keyfile::PASSPHRASE_ENV)
+        .ok()
+        .filter(|p| !p.is_empty())
+        .map(Zeroizing::new)
+}
+
+/// Directory of keys replaced by `devapack key rotate`, kept to verify older signatures.
+pub fn retired_keys_dir() -> Result<PathBuf, String> {
+    Ok(key_path()?.with_file_name("retired"))
+}
+
+/// Public keys of the local key and of every retired key, current key first.
+pub fn local_public_keys() -> Vec<PublicKey> {
+    let mut keys: Vec<PublicKey> = local_public_key().into_iter().collect();
+    let Ok(dir) = retired_keys_dir() else {
+        return keys;
+    };
+    let mut retired: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    retired.sort();
+    for path in retired {
+        if let Some(public) = std::fs::read(&path)
+            .ok()
+            .and_then(|b| public_key_from_file_bytes(&b).ok())
+        {
+            keys.push(public);
+        }
+    }
+    keys
+}
+
+/// Short, stable identifier of a public key: the first 16 hex digits of its SHA-256.
+///
+/// ### Parameters
+/// - `public`: The Ed25519 public key
+///
+pub fn fingerprint(public: &PublicKey) -> String {
+    hex::encode(&Sha256::digest(public.to_bytes())[..8])
+}
+
+/// Parses a base64 Ed25519 public key.
+///
+/// ### Parameters
+/// - `b64`: The key, as printed by devapack or sent to Forge
+///
+pub fn decode_public_key(b64: &str) -> Result<PublicKey, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(b64.trim())
+        .map_err(|e| format!("Public key is not valid base64: {}", e))?;
+    PublicKey::from_bytes(&bytes).map_err(|e| format!("Invalid Ed25519 public key: {}", e))
+}
+
+/// Parses a public key given on the command line: base64, minisign, or a file holding
+/// either or the raw 32 bytes.
+///
+/// ### Parameters
+/// - `cwd`: The directory relative file paths start from
+/// - `arg`: The key or key file
+///
+pub fn public_key_from_arg(cwd: &str, arg: &str) -> Result<PublicKey, String> {
+    let path = Path::new(cwd).join(arg);
+    let text = if path.is_file() {
+        let bytes = std::fs::read(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if bytes.len() == 32 {
+            return PublicKey::from_bytes(&bytes)
+                .map_err(|e| format!("Invalid Ed25519 public key: {}", e));
+        }
+        String::from_utf8_lossy(&bytes).to_string()
+    } else {
+        arg.to_string()
+    };
+    decode_public_key(&text).or_else(|e| {
+        minisign::decode_public_key(&text)
+            .map(|(_, public)| public)
+            .map_err(|_| e)
+    })
+}
+
+/// Checks a base64 Ed25519 signature of `message`, as written by `sign_file`.
+///
+/// ### Parameters
+/// - `public`: The signer's public key
+/// - `message`: The signed bytes, usually a SHA-256 digest
+/// - `sig_b64`: The signature
+///
+pub fn verify_bytes(public: &PublicKey, message: &[u8], sig_b64: &str) -> Result<bool, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(sig_b64.trim())
+        .map_err(|e| format!("Signature is not valid base64: {}", e))?;
+    let sig = Signature::from_bytes(&bytes).map_err(|e| format!("Invalid signature: {}", e))?;
+    Ok(public.verify_strict(message, &sig).is_ok())
+}
+
+/// A detached signature, stored as TOML in `<archive>.sig` next to the signed file.
+///
+/// `signature` covers the SHA-256 of the file. Only `signature` is required when reading,
+/// so a bare base64 signature is accepted too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    pub signature: String,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// Fingerprint of `public_key`
+    #[serde(default)]
+    pub key_id: Option<String>,
+    /// RFC 3339 time of signing
+    #[serde(default)]
+    pub signed_at: Option<String>,
+}
+
+/// Path of the detached signature of a file: `<file>.sig`.
+///
+/// ### Parameters
+/// - `file`: The signed file
+///
+pub fn signature_path(file: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.sig", file.display()))
+}
+
+/// Signs the SHA-256 of a file with the local key and writes `<file>.sig`.
+///
+/// ### Parameters
+/// - `file`: The file to sign, usually a build archive
+///
+pub fn sign_file(file: &Path) -> Result<DetachedSignature, String> {
+    let bytes =
+        std::fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let kp = load_keypair()?;
+    let sig: Signature = kp.sign(&Sha256::digest(&bytes));
+    let detached = DetachedSignature {
+        signature: general_purpose::STANDARD.encode(sig.to_bytes()),
+        public_key: Some(general_purpose::STANDARD.encode(kp.public.to_bytes())),
+        key_id: Some(fingerprint(&kp.public)),
+        signed_at: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    let txt =
+        toml::to_string(&detached).map_err(|e| format!("Failed to serialize signature: {}", e))?;
+    let sig_path = signature_path(file);
+    std::fs::write(&sig_path, txt)
+        .map_err(|e| format!("Failed to write {}: {}", sig_path.display(), e))?;
+    Ok(detached)
+}
+
+/// Reads a detached signature file, accepting TOML or a bare base64 signature.
+///
+/// ### Parameters
+/// - `path`: The signature file
+///
+pub fn read_signature(path: &Path) -> Result<DetachedSignature, String> {
+    let txt = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read signature {}: {}", path.display(), e))?;
+    if let Ok(parsed) = toml::from_str::<DetachedSignature>(&txt) {
+        return Ok(parsed);
+    }
+    let trimmed = txt.trim();
+    if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+        return Err(format!(
+            "Signature file {} is neither a base64 signature nor TOML with a `signature` field",
+            path.display()
+        ));
+    }
+    Ok(DetachedSignature {
+        signature: trimmed.to_string(),
+        public_key: None,
+        key_id: None,
+        signed_at: None,
+    })
 }
@@ -1,15 +1,34 @@
-use crate::utils::logger::{LogLevel, Logger};
-use indicatif::{ProgressBar, ProgressStyle};
-use std::cell::Cell;
+use crate::utils::logger::{self, LogLevel, Logger};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::sync::Mutex;
 use std::time::Duration;
 
+thread_local! {
+    /// Progress line of the item a `run_parallel` worker is processing.
+    static WORKER_LINE: RefCell<Option<ProgressBar>> = const { RefCell::new(None) };
+}
+
 pub struct Spinner {
     bar: ProgressBar,
     active: Cell<bool>,
+    /// Whether `bar` is a `run_parallel` line, which outlives this spinner
+    shared: bool,
 }
 
 impl Spinner {
     pub fn new(message: impl Into<String>) -> Self {
+        // Inside run_parallel, steps update the worker's line instead of drawing their own
+        if let Some(line) = WORKER_LINE.with(|l| l.borrow().clone()) {
+            line.set_message(message.into());
+            return Spinner {
+                bar: line,
+                active: Cell::new(true),
+                shared: true,
+            };
+        }
+
         let bar = ProgressBar::new_spinner();
         let style = ProgressStyle::with_template("{spinner} {msg}")
             .unwrap_or_else(|_| ProgressStyle::default_spinner())
@@ -21,6 +40,13 @@ impl Spinner {
         Spinner {
             bar,
             active: Cell::new(true),
+            shared: false,
+        }
+    }
+
+    fn clear(&self) {
+        if !self.shared {
+            self.bar.finish_and_clear();
         }
     }
 
@@ -41,7 +67,7 @@ impl Spinner {
     pub fn succeed(&self, message: impl Into<String>) {
         if self.active.get() {
             // Clear spinner then emit a structured success log via Logger
-            self.bar.finish_and_clear();
+            self.clear();
             Logger::new().log_message(LogLevel::Success, &message.into().to_string());
             self.active.set(false);
         }
@@ -50,7 +76,7 @@ impl Spinner {
     pub fn fail(&self, message: impl Into<String>) {
         if self.active.get() {
             // Clear spinner then emit a structured error log via Logger
-            self.bar.finish_and_clear();
+            self.clear();
             Logger::new().log_message(LogLevel::Error, &message.into().to_string());
             self.active.set(false);
         }
@@ -58,7 +84,7 @@ impl Spinner {
 
     pub fn finish_and_clear(&self) {
         if self.active.get() {
-            self.bar.finish_and_clear();
+            self.clear();
             self.active.set(false);
         }
     }
@@ -66,7 +92,7 @@ impl Spinner {
 
 impl Drop for Spinner {
     fn drop(&mut self) {
-        if self.active.get() {
+        if self.active.get() && !self.shared {
             self.bar.abandon();
             // Note: active is Cell but we have &mut self here, so set directly
             self.active.set(false);
@@ -103,3 +129,69 @@ where
 {
     run_step(start_message, |_| success_message.to_string(), action)
 }
+
+/// Runs `work` on every item with up to `jobs` worker threads and returns the results in
+/// the order of `items`.
+///
+/// Each running item gets its own spinner line, which the spinners of its steps update.
+/// What an item logs is held back and printed as one block when it finishes, so output
+/// of concurrent items does not interleave. With `jobs <= 1` items run one after the
+/// other on the calling thread with regular output.
+///
+/// ### Parameters
+/// - `items`: The work items
+/// - `jobs`: The maximum number of items processed at once
+/// - `label`: The text shown in front of an item's spinner line
+/// - `work`: The processing of one item
+///
+pub fn run_parallel<I, R, L, F>(items: Vec<I>, jobs: usize, label: L, work: F) -> Vec<R>
+where
+    I: Send,
+    R: Send,
+    L: Fn(&I) -> String + Sync,
+    F: Fn(I) -> R + Sync,
+{
+    if jobs <= 1 || items.len() <= 1 {
+        return items.into_iter().map(work).collect();
+    }
+
+    let total = items.len();
+    let multi = MultiProgress::new();
+    let style = ProgressStyle::with_template("{spinner} {prefix:.bold} {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]);
+    let queue: Mutex<VecDeque<(usize, I)>> = Mutex::new(items.into_iter().enumerate().collect());
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..total).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(total) {
+            scope.spawn(|| {
+                loop {
+                    let Some((index, item)) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let line = multi.add(ProgressBar::new_spinner());
+                    line.set_style(style.clone());
+                    line.set_prefix(label(&item));
+                    line.enable_steady_tick(Duration::from_millis(80));
+
+                    WORKER_LINE.with(|l| *l.borrow_mut() = Some(line.clone()));
+                    let (result, output) = logger::capture(|| work(item));
+                    WORKER_LINE.with(|l| *l.borrow_mut() = None);
+
+                    line.finish_and_clear();
+                    multi.remove(&line);
+                    multi.suspend(|| print!("{}", output));
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.expect("every queued item is processed"))
+        .collect()
+}
@@ -0,0 +1,392 @@
+use crate::trust::store::{self, Trust, TrustStore};
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    minisign::{self, MinisignSignature},
+    signing::{self, DetachedSignature},
+};
+use ed25519_dalek::PublicKey;
+use flate2::bufread::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Exit code of `devapack verify` when the archive does not match its signature or manifest.
+///
+/// Other failures (unreadable files, no signature, no key) exit with 1.
+pub const EXIT_TAMPERED: i32 = 3;
+
+/// Where a key comes from when it is read from the signature file itself.
+const KEY_FROM_SIGNATURE_FILE: &str = "the signature file";
+
+/// Outcome of a verification that could run to completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Valid,
+    Tampered,
+    /// The signature is valid, but made with a key nobody vouched for
+    Untrusted,
+}
+
+/// A signature file in one of the formats `devapack verify` reads.
+enum SignatureFile {
+    Detached(DetachedSignature),
+    Minisign(MinisignSignature),
+}
+
+/// Checks an archive offline: its Ed25519 signature and the per-file checksums of its manifest.
+///
+/// A devapack signature may cover the SHA-256 of the archive or of the uncompressed tar,
+/// as devapack signs both when submitting. Minisign signatures are read too.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `archive`: The archive to check
+/// - `sig`: The signature file, defaults to `<archive>.sig`, then `<archive>.minisig`
+/// - `pubkey`: The signer's public key, as base64, a key file or a minisign public key
+/// - `publisher`: The publisher whose trusted keys must have signed, defaults to the manifest's
+/// - `trust_new`: Trust the signer without asking when the publisher has no trusted key yet
+///
+pub fn run_verify(
+    cwd: &str,
+    archive: &str,
+    sig: Option<&str>,
+    pubkey: Option<&str>,
+    publisher: Option<&str>,
+    trust_new: bool,
+) -> Result<Verdict, String> {
+    let archive_path = Path::new(cwd).join(archive);
+    let bytes = fs::read(&archive_path)
+        .map_err(|e| format!("Failed to read {}: {}", archive_path.display(), e))?;
+    let sha = Sha256::digest(&bytes);
+    let tar_bytes = gunzip(&bytes);
+    // A corrupted tar is as much tampering as a wrong checksum
+    let files = tar_bytes.as_deref().map(read_tar_files);
+    let publisher = publisher.map(str::to_string).or_else(|| match &files {
+        Some(Ok(files)) => manifest_publisher(files),
+        _ => None,
+    });
+
+    let sig_file = match sig {
+        Some(s) => read_signature_file(&Path::new(cwd).join(s))?,
+        None => {
+            let detached = signing::signature_path(&archive_path);
+            let mini = minisign::signature_path(&archive_path);
+            let path = if !detached.exists() && mini.exists() {
+                mini
+            } else {
+                detached
+            };
+            read_signature_file(&path).map_err(|e| format!("{} (pass --sig to point at it)", e))?
+        }
+    };
+    let embedded_key = match &sig_file {
+        SignatureFile::Detached(d) => d
+            .public_key
+            .as_deref()
+            .map(signing::decode_public_key)
+            .transpose()?,
+        SignatureFile::Minisign(_) => None,
+    };
+    let pinned = match &publisher {
+        Some(p) => TrustStore::load()?.keys(p),
+        None => Vec::new(),
+    };
+    let candidates = resolve_public_keys(cwd, pubkey, &pinned)?;
+
+    let mut digests = vec![sha.to_vec()];
+    if let Some(tar) = &tar_bytes {
+        digests.push(Sha256::digest(tar).to_vec());
+    }
+    let verifies = |public: &PublicKey| -> Result<bool, String> {
+        match &sig_file {
+            SignatureFile::Detached(d) => {
+                let mut matched = false;
+                for digest in &digests {
+                    matched |= signing::verify_bytes(public, digest, &d.signature)?;
+                }
+                Ok(matched)
+            }
+            SignatureFile::Minisign(m) => minisign::verify(m, public, &bytes),
+        }
+    };
+    let mut signer = None;
+    for (public, source) in &candidates {
+        if verifies(public)? {
+            signer = Some((*public, *source));
+            break;
+        }
+    }
+    // Anyone can embed their own key in a signature file, so that key is only a candidate
+    // for a publisher without trusted keys, and only counts once the user trusts it below
+    let first_use = embedded_key.filter(|_| {
+        signer.is_none() && pubkey.is_none() && pinned.is_empty() && publisher.is_some()
+    });
+    if let Some(embedded) = first_use
+        && verifies(&embedded)?
+    {
+        signer = Some((embedded, KEY_FROM_SIGNATURE_FILE));
+    }
+    if signer.is_none() && candidates.is_empty() {
+        return Err(match embedded_key {
+            Some(embedded) => format!(
+                "The signature names key {}, which is not trusted: pass --pubkey, or --publisher to trust it for a publisher",
+                signing::fingerprint(&embedded)
+            ),
+            None => "No public key to verify with: pass --pubkey".to_string(),
+        });
+    }
+    let signed = signer.is_some();
+
+    let manifest = match &files {
+        Some(Ok(files)) => check_manifest(files).unwrap_or_else(|e| ManifestCheck {
+            checked: 0,
+            mismatches: vec![e],
+        }),
+        Some(Err(e)) => ManifestCheck {
+            checked: 0,
+            mismatches: vec![e.clone()],
+        },
+        None => ManifestCheck::default(),
+    };
+    // Only a valid signature says who signed, so only then is the signer checked
+    let trust = match (&publisher, signer) {
+        (Some(p), Some((public, _))) => Some(store::check_signer(p, &public, trust_new)?),
+        _ => None,
+    };
+    // Only a key the user supplied, or one of their own, may sign for an unpinned publisher
+    let untrusted = matches!(
+        (&trust, signer),
+        (Some(Trust::Unpinned), Some((_, KEY_FROM_SIGNATURE_FILE)))
+    );
+
+    let mut trace = vec![
+        format!("Archive: {}", archive_path.display()),
+        format!("SHA-256: {}", hex::encode(sha)),
+        match signer {
+            Some((public, source)) => format!(
+                "Signer: {} (key from {})",
+                signing::fingerprint(&public),
+                source
+            ),
+            None => format!(
+                "Signer: none of the {} key(s) from {}",
+                candidates.len(),
+                describe_sources(&candidates)
+            ),
+        },
+        format!("Signature: {}", if signed { "valid" } else { "INVALID" }),
+    ];
+    match &sig_file {
+        SignatureFile::Detached(d) => {
+            if let Some(signed_at) = &d.signed_at {
+                trace.push(format!("Signed at: {}", signed_at));
+            }
+        }
+        SignatureFile::Minisign(m) => {
+            trace.push(format!(
+                "Format: minisign{}, key id {}",
+                if m.prehashed { " (prehashed)" } else { "" },
+                minisign::key_id_hex(&m.key_id)
+            ));
+            trace.push(format!("Trusted comment: {}", m.trusted_comment));
+        }
+    }
+    match (manifest.checked, manifest.mismatches.is_empty()) {
+        (0, true) => trace.push("Manifest: no per-file checksums embedded".to_string()),
+        (n, true) => trace.push(format!("Manifest: {} file(s) match", n)),
+        (_, false) => {
+            for m in &manifest.mismatches {
+                trace.push(format!("Manifest: {}", m));
+            }
+        }
+    }
+    if let (Some(p), Some(trust)) = (&publisher, &trust) {
+        trace.push(match trust {
+            Trust::Pinned => format!("Trust: a trusted key of '{}'", p),
+            Trust::PinnedOnFirstUse => format!("Trust: now trusted for '{}' (first use)", p),
+            Trust::Unpinned if untrusted => format!(
+                "Trust: NOT trusted; '{}' has no trusted key, so pass --trust-new or --pubkey, or run 'devapack trust add'",
+                p
+            ),
+            Trust::Unpinned => format!(
+                "Trust: '{}' has no trusted key; run 'devapack trust add' to pin one",
+                p
+            ),
+            Trust::Mismatch(keys) => format!(
+                "Trust: NOT a trusted key of '{}', which is pinned to {}",
+                p,
+                keys.join(", ")
+            ),
+        });
+    }
+    let refs: Vec<&str> = trace.iter().map(|s| s.as_str()).collect();
+
+    let logger = Logger::new();
+    if let Some(Trust::Mismatch(_)) = trust {
+        logger.log_message_with_trace(
+            LogLevel::Error,
+            "Archive failed verification: it is signed by a key not trusted for its publisher",
+            refs,
+        );
+        Ok(Verdict::Tampered)
+    } else if untrusted {
+        logger.log_message_with_trace(
+            LogLevel::Error,
+            "Archive not verified: its signer is not trusted",
+            refs,
+        );
+        Ok(Verdict::Untrusted)
+    } else if signed && manifest.mismatches.is_empty() {
+        logger.log_message_with_trace(LogLevel::Success, "Archive verified", refs);
+        Ok(Verdict::Valid)
+    } else {
+        logger.log_message_with_trace(
+            LogLevel::Error,
+            "Archive failed verification: it was modified or signed by another key",
+            refs,
+        );
+        Ok(Verdict::Tampered)
+    }
+}
+
+/// Reads a devapack or minisign signature file.
+fn read_signature_file(path: &Path) -> Result<SignatureFile, String> {
+    match fs::read_to_string(path) {
+        Ok(txt) if minisign::is_signature(&txt) => {
+            Ok(SignatureFile::Minisign(minisign::parse_signature(&txt)?))
+        }
+        _ => signing::read_signature(path).map(SignatureFile::Detached),
+    }
+}
+
+/// Picks the keys to verify with: `--pubkey` alone, or else the publisher's trusted keys
+/// and the local key with the keys it replaced, each with where it comes from.
+fn resolve_public_keys(
+    cwd: &str,
+    pubkey: Option<&str>,
+    pinned: &[PublicKey],
+) -> Result<Vec<(PublicKey, &'static str)>, String> {
+    if let Some(arg) = pubkey {
+        return Ok(vec![(signing::public_key_from_arg(cwd, arg)?, "--pubkey")]);
+    }
+    let mut keys: Vec<(PublicKey, &'static str)> = pinned
+        .iter()
+        .map(|k| (*k, "the publisher's trusted keys"))
+        .collect();
+    for local in signing::local_public_keys() {
+        if !pinned.contains(&local) {
+            keys.push((local, "the local signing keys"));
+        }
+    }
+    Ok(keys)
+}
+
+/// The distinct sources of the candidate keys, for messages.
+fn describe_sources(candidates: &[(PublicKey, &'static str)]) -> String {
+    let mut sources: Vec<&str> = Vec::new();
+    for (_, source) in candidates {
+        if !sources.contains(source) {
+            sources.push(source);
+        }
+    }
+    sources.join(" and ")
+}
+
+/// Per-file checksum results of an archive's manifest.
+#[derive(Debug, Default)]
+struct ManifestCheck {
+    checked: usize,
+    mismatches: Vec<String>,
+}
+
+/// Regular files of a tar, by path.
+fn read_tar_files(tar_bytes: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    let mut archive = tar::Archive::new(tar_bytes);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive entries: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|e| format!("Invalid path in archive: {}", e))?
+            .to_string_lossy()
+            .trim_start_matches("./")
+            .to_string();
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read {} from archive: {}", path, e))?;
+        files.insert(path, data);
+    }
+    Ok(files)
+}
+
+/// Publisher declared by an embedded plugin.toml or bank.toml.
+fn manifest_publisher(files: &BTreeMap<String, Vec<u8>>) -> Option<String> {
+    ["plugin", "bank"].iter().find_map(|kind| {
+        let manifest = files.get(&format!("{}.toml", kind))?;
+        let doc: toml::Value = toml::from_str(&String::from_utf8_lossy(manifest)).ok()?;
+        Some(doc.get(kind)?.get("publisher")?.as_str()?.to_string())
+    })
+}
+
+/// Compares the checksums of an embedded manifest with the archive files: the
+/// `[[binaries]]` of a plugin.toml, or the `[[triggers]]` of a bank.toml.
+fn check_manifest(files: &BTreeMap<String, Vec<u8>>) -> Result<ManifestCheck, String> {
+    let mut check = ManifestCheck::default();
+    // Trigger paths are relative to the bank's audio directory
+    let Some((manifest, table, dir)) = [
+        ("plugin.toml", "binaries", ""),
+        ("bank.toml", "triggers", "audio/"),
+    ]
+    .into_iter()
+    .find(|(manifest, _, _)| files.contains_key(*manifest)) else {
+        return Ok(check);
+    };
+    let doc: toml::Value = toml::from_str(&String::from_utf8_lossy(&files[manifest]))
+        .map_err(|e| format!("Invalid {} in archive: {}", manifest, e))?;
+    let entries = doc
+        .get(table)
+        .and_then(|b| b.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for entry in entries {
+        let (Some(path), Some(expected)) = (
+            entry.get("path").and_then(|p| p.as_str()),
+            entry.get("sha256").and_then(|s| s.as_str()),
+        ) else {
+            continue;
+        };
+        let path = format!("{}{}", dir, path.trim_start_matches("./"));
+        check.checked += 1;
+        match files.get(&path) {
+            Some(data) if hex::encode(Sha256::digest(data)) == expected.to_lowercase() => {}
+            Some(_) => check
+                .mismatches
+                .push(format!("{} does not match its sha256", path)),
+            None => check
+                .mismatches
+                .push(format!("{} is listed but missing", path)),
+        }
+    }
+    Ok(check)
+}
+
+/// Uncompressed content of a gzip file, or `None` when the bytes are not exactly one
+/// gzip stream (data appended after it would otherwise go unnoticed).
+fn gunzip(bytes: &[u8]) -> Option<Vec<u8>> {
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return None;
+    }
+    let mut out = Vec::new();
+    let mut decoder = GzDecoder::new(bytes);
+    decoder.read_to_end(&mut out).ok()?;
+    decoder.into_inner().is_empty().then_some(out)
+}